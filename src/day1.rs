@@ -1,10 +1,20 @@
-struct PairIterator {
+use anyhow::{anyhow, Error};
+use rand::{seq::SliceRandom, Rng};
+
+use crate::error::{Location, ParseError};
+use crate::input::integers;
+use crate::runner::{Example, Solution};
+
+const INPUT: &str = include_str!("day1.input");
+const EXAMPLE: &str = include_str!("day1.example");
+
+pub struct PairIterator {
     vec: Vec<usize>,
     i_idx: usize,
     j_idx: usize,
 }
 impl PairIterator {
-    fn new(vec: Vec<usize>) -> Self {
+    pub fn new(vec: Vec<usize>) -> Self {
         Self {
             vec,
             i_idx: 0,
@@ -16,6 +26,9 @@ impl Iterator for PairIterator {
     type Item = (usize, usize);
 
     fn next(&mut self) -> Option<Self::Item> {
+        if self.vec.len() < 2 {
+            return None;
+        }
         let last_idx = self.vec.len() - 1;
         if self.i_idx < last_idx {
             if self.j_idx < last_idx {
@@ -32,14 +45,14 @@ impl Iterator for PairIterator {
     }
 }
 
-struct TriadIterator {
+pub struct TriadIterator {
     vec: Vec<usize>,
     i_idx: usize,
     j_idx: usize,
     k_idx: usize,
 }
 impl TriadIterator {
-    fn new(vec: Vec<usize>) -> Self {
+    pub fn new(vec: Vec<usize>) -> Self {
         Self {
             vec,
             i_idx: 0,
@@ -52,6 +65,9 @@ impl Iterator for TriadIterator {
     type Item = (usize, usize, usize);
 
     fn next(&mut self) -> Option<Self::Item> {
+        if self.vec.len() < 3 {
+            return None;
+        }
         let last_idx = self.vec.len() - 1;
         if self.i_idx < last_idx {
             if self.j_idx < last_idx {
@@ -88,7 +104,7 @@ impl Iterator for TriadIterator {
     }
 }
 
-trait Pairs {
+pub trait Pairs {
     /// Return a lazy iterator over unique pairs from the vec
     fn pairs(self) -> PairIterator;
 }
@@ -99,7 +115,7 @@ impl Pairs for Vec<usize> {
     }
 }
 
-trait Triads {
+pub trait Triads {
     /// Return a lazy iterator over unique triads from the vec
     fn triads(self) -> TriadIterator;
 }
@@ -111,41 +127,135 @@ impl Triads for Vec<usize> {
     }
 }
 
-fn get_day_one_input() -> Vec<usize> {
-    vec![
-        1384, 1396, 1072, 1903, 1387, 1763, 1600, 1862, 1992, 1585, 1909, 1352, 1288,
-        1910, 1070, 1421, 1802, 1669, 1059, 1235, 1854, 1722, 1275, 198, 1476, 1588,
-        1708, 1217, 1596, 1355, 1566, 1973, 1335, 1480, 1115, 1272, 1998, 1821, 2007,
-        1721, 1885, 1420, 1412, 1487, 1941, 1835, 1558, 1061, 1582, 1940, 1942, 1210,
-        1350, 1175, 1047, 1456, 1548, 1110, 1510, 1995, 1644, 1968, 1297, 1198, 1471,
-        1360, 1363, 1528, 1393, 1365, 1837, 1886, 2001, 1161, 1349, 1787, 988, 1331,
-        1960, 1607, 1324, 97, 1986, 1955, 1773, 1443, 1852, 1368, 1050, 1378, 1239,
-        1750, 1868, 816, 1965, 1661, 1728, 1981, 984, 1037, 1525, 1789, 1318, 1952,
-        1359, 1358, 1869, 1641, 1240, 1542, 1959, 1022, 1475, 1733, 1081, 1889, 1138,
-        1757, 1736, 1723, 1543, 1820, 1128, 1039, 1683, 1477, 1375, 1499, 676, 1195,
-        1250, 220, 1581, 1328, 1187, 1485, 1216, 1769, 1139, 1064, 1908, 1516, 1490,
-        1419, 1749, 1347, 1758, 1024, 1053, 1842, 1861, 1403, 1966, 1546, 1134, 1593,
-        1734, 1916, 1867, 1101, 1126, 1301, 1841, 1515, 1244, 1401, 1637, 1054, 1309,
-        1933, 1512, 1263, 1815, 1634, 1823, 1295, 1583, 1104, 1765, 1850, 1311, 1692,
-        1905, 1149, 1780, 1330, 1666, 996, 1913, 1140, 1089, 1484, 1356, 1296, 1323,
-        1160, 1881, 1123, 1166, 1929,
-    ]
-}
-
-pub fn day_one_solution_one() -> usize {
-    get_day_one_input()
+/// Generate an expense report of `count` entries where exactly one pair and
+/// exactly one triad of entries add up to 2020
+///
+/// The planted pair and triad are the only entries under 1010. Every other
+/// entry is over 1010, so no two of them add up to 2020, and any value that
+/// would make 2020 along with the planted entries is left out.
+pub fn generate_expenses<R: Rng>(count: usize, rng: &mut R) -> Result<String, Error> {
+    if count < 5 {
+        return Err(anyhow!(
+            "An expense report needs at least 5 entries, not {}",
+            count
+        ));
+    }
+    let small = loop {
+        let (a, x, y) = (
+            rng.gen_range(1..1010),
+            rng.gen_range(1..1010),
+            rng.gen_range(1..1010),
+        );
+        let z = 2020usize.saturating_sub(x + y);
+        let small = [a, x, y, z];
+        let distinct = (1..small.len()).all(|i| !small[i..].contains(&small[i - 1]));
+        if (1..1010).contains(&z) && distinct {
+            break small;
+        }
+    };
+    // anything that adds up to 2020 with one or two of the small entries
+    let forbidden = (0..small.len())
+        .flat_map(|i| (i..small.len()).map(move |j| (i, j)))
+        .map(|(i, j)| match i == j {
+            true => 2020 - small[i],
+            false => 2020usize.saturating_sub(small[i] + small[j]),
+        })
+        .collect::<Vec<usize>>();
+    let mut entries = small.to_vec();
+    entries.push(2020 - small[0]);
+    while entries.len() < count {
+        let entry = rng.gen_range(1011..2020);
+        if !forbidden.contains(&entry) {
+            entries.push(entry);
+        }
+    }
+    entries.shuffle(rng);
+    Ok(entries.iter().map(|e| format!("{}\n", e)).collect())
+}
+
+/// The entries in an expense report, of which there has to be at least one
+fn expenses(input: &str) -> Result<Vec<usize>, ParseError> {
+    let entries = integers::<usize>(input)?;
+    match entries.is_empty() {
+        true => Err(ParseError::Missing {
+            at: Location::end_of(input),
+            what: "expense entries".into(),
+        }),
+        false => Ok(entries),
+    }
+}
+
+fn part_one(input: &str) -> Result<usize, Error> {
+    expenses(input)?
         .pairs()
-        .filter(|(i, j)| i + j == 2020)
-        .nth(0)
+        .find(|(i, j)| i + j == 2020)
         .map(|(i, j)| i * j)
-        .unwrap()
+        .ok_or_else(|| anyhow!("No two entries add up to 2020"))
 }
 
-pub fn day_one_solution_two() -> usize {
-    get_day_one_input()
+fn part_two(input: &str) -> Result<usize, Error> {
+    expenses(input)?
         .triads()
-        .filter(|(i, j, k)| i + j + k == 2020)
-        .nth(0)
+        .find(|(i, j, k)| i + j + k == 2020)
         .map(|(i, j, k)| i * j * k)
-        .unwrap()
+        .ok_or_else(|| anyhow!("No three entries add up to 2020"))
+}
+
+pub fn day_one_solution_one() -> Result<usize, Error> {
+    part_one(INPUT)
+}
+
+pub fn day_one_solution_two() -> Result<usize, Error> {
+    part_two(INPUT)
+}
+
+/// Both parts, for the runner
+pub const SOLUTIONS: [Solution; 2] = [
+    Solution {
+        day: 1,
+        part: 1,
+        input: INPUT,
+        solve: part_one,
+        examples: &[Example {
+            input: EXAMPLE,
+            answer: 514579,
+        }],
+    },
+    Solution {
+        day: 1,
+        part: 2,
+        input: INPUT,
+        solve: part_two,
+        examples: &[Example {
+            input: EXAMPLE,
+            answer: 241861950,
+        }],
+    },
+];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn short_reports_have_no_pairs_or_triads() {
+        assert_eq!(Vec::new().pairs().count(), 0);
+        assert_eq!(vec![1].pairs().count(), 0);
+        assert_eq!(vec![1, 2].pairs().collect::<Vec<_>>(), vec![(1, 2)]);
+        assert_eq!(vec![1, 2].triads().count(), 0);
+        assert_eq!(vec![1, 2, 3].triads().collect::<Vec<_>>(), vec![(1, 2, 3)]);
+        assert_eq!(vec![1, 2, 3, 4].triads().count(), 4);
+    }
+
+    #[test]
+    fn empty_reports_are_errors() {
+        for solve in &[part_one, part_two] {
+            let err = solve("\n").unwrap_err();
+            assert_eq!(
+                err.downcast_ref::<ParseError>().unwrap().to_string(),
+                "Missing expense entries at line 2, column 1"
+            );
+        }
+        assert!(part_one("1010").is_err());
+    }
 }
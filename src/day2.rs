@@ -1,24 +1,28 @@
+use rand::Rng;
 use std::convert::TryFrom;
 use std::ops::Range;
 
-use anyhow;
+use crate::error::{Location, ParseError};
+use crate::input::parse_lines;
+use crate::runner::{Example, Solution};
 
-static INPUT: &'static str = include_str!("day2.input");
+const INPUT: &str = include_str!("day2.input");
+const EXAMPLE: &str = include_str!("day2.example");
 
-struct PasswordRule {
+pub struct PasswordRule {
     allowed: Range<usize>,
     character: char,
 }
 impl PasswordRule {
-    fn new(allowed: Range<usize>, character: char) -> Self {
+    pub fn new(allowed: Range<usize>, character: char) -> Self {
         Self { allowed, character }
     }
-    fn is_valid_first_question<T: AsRef<str>>(&self, password: &T) -> bool {
+    pub fn is_valid_first_question<T: AsRef<str>>(&self, password: &T) -> bool {
         let pw = password.as_ref();
         self.allowed
             .contains(&pw.chars().filter(|c| c == &self.character).count())
     }
-    fn is_valid_second_question<T: AsRef<str>>(&self, password: &T) -> bool {
+    pub fn is_valid_second_question<T: AsRef<str>>(&self, password: &T) -> bool {
         let pw = password.as_ref();
         // our range is set to [x through y + 1]. They want to check the
         // xth and yth items, so we need index (start - 1) for the first item,
@@ -39,129 +43,194 @@ impl PasswordRule {
             // so we unwrap_or false
             .unwrap_or(false)
     }
+
+    /// Parse a rule like `1-3 a`, which is a slice of `line`, so that errors
+    /// can show the whole line
+    fn parse_within(line: &str, rule: &str) -> Result<Self, ParseError> {
+        let mut parts = rule.split_whitespace();
+        let range = parts.next().ok_or_else(|| ParseError::Missing {
+            at: Location::of(line, &rule[rule.len()..]),
+            what: "range".into(),
+        })?;
+        let character = parts.next().ok_or_else(|| ParseError::Missing {
+            at: Location::of(line, &rule[rule.len()..]),
+            what: "character".into(),
+        })?;
+        // the character has to be exactly one character
+        let mut chars = character.chars();
+        let c = match (chars.next(), chars.next(), parts.next()) {
+            (Some(c), None, None) => c,
+            (_, _, Some(extra)) => {
+                return Err(ParseError::Malformed {
+                    at: Location::of(line, extra),
+                    what: "rule".into(),
+                    expected: "a range and a character".into(),
+                })
+            }
+            _ => {
+                return Err(ParseError::Malformed {
+                    at: Location::of(line, character),
+                    what: "character".into(),
+                    expected: "a single character".into(),
+                })
+            }
+        };
+        // split the range on the dash
+        let (start, end) = range
+            .find('-')
+            .map(|idx| (&range[..idx], &range[idx + 1..]))
+            .ok_or_else(|| ParseError::Malformed {
+                at: Location::of(line, range),
+                what: "range".into(),
+                expected: "two numbers separated by a -".into(),
+            })?;
+        let number = |n: &str| {
+            n.parse::<usize>().map_err(|_| ParseError::InvalidNumber {
+                at: Location::of(line, n),
+            })
+        };
+        // we add 1 to the end b/c Range is [), and the test is []
+        let (start, end) = (number(start)?, number(end)? + 1);
+        // the second question indexes from 1, and needs a start before the end
+        if start == 0 || start >= end {
+            return Err(ParseError::Invalid {
+                at: Location::of(line, range),
+                what: "range".into(),
+            });
+        }
+        Ok(PasswordRule::new(Range { start, end }, c))
+    }
 }
 // See https://github.com/rust-lang/rust/issues/50133 for context on the wrapper
-struct PasswordRuleWrapper<T: AsRef<str>>(T);
+pub struct PasswordRuleWrapper<T: AsRef<str>>(pub T);
 impl<T> TryFrom<PasswordRuleWrapper<T>> for PasswordRule
 where
     T: AsRef<str>,
 {
-    type Error = anyhow::Error;
+    type Error = ParseError;
 
     fn try_from(value: PasswordRuleWrapper<T>) -> Result<Self, Self::Error> {
-        let val = Some(value.0.as_ref().trim().split_whitespace());
-        val
-            // ensure we've got two values
-            .and_then(|mut v| v.next().and_then(|range| Some((range, v.next()?))))
-            // convert the second value to a char
-            .and_then(|(r, c)| Some((r, c.chars().next()?)))
-            // split the first value on the dash
-            .map(|(r, c)| (r.split('-'), c))
-            // ensure the first value contained two items
-            .and_then(|(mut r, c)| {
-                r.next().and_then(|start| Some(((start, r.next()?), c)))
-            })
-            .ok_or(anyhow::anyhow!("Bad input"))
-            // convert the items of the first value to integers
-            // we add 1 to the end b/c Range is [), and the test is []
-            .and_then(|((start, end), c)| {
-                Ok(((start.parse::<usize>()?, end.parse::<usize>()? + 1), c))
-            })
-            // construct the password rule
-            .map(|((start, end), c)| PasswordRule::new(Range { start, end }, c))
+        let rule = value.0.as_ref();
+        PasswordRule::parse_within(rule, rule)
     }
 }
 
-pub fn day_two_solution_one() -> anyhow::Result<usize> {
-    INPUT
-        .lines()
-        // for each line
-        .map(|ln| {
-            // split it on the colon
-            let mut split = ln.split(':');
-            split
-                // grab the first item of the split
-                .next()
-                // remove leading and trailing whitespace
-                .map(str::trim)
-                // convert from option to result
-                .ok_or(anyhow::anyhow!("Input must contain a :"))
-                // attempt to convert to password rule
-                .and_then(|pw_rule| {
-                    PasswordRule::try_from(PasswordRuleWrapper(pw_rule))
-                })
-                // if we succeeded, chain to getting the password
-                .and_then(|pw_rule| {
-                    split
-                        // grab the second item out of the split
-                        .next()
-                        // trim it of whitespace
-                        .map(str::trim)
-                        // convert from option to result
-                        .ok_or(anyhow::anyhow!(
-                            "Input must contain a password after the :"
-                        ))
-                        // return a tuple of rule and password
-                        .map(|pw| (pw_rule, pw))
+/// Parse one line of the password file, like `1-3 a: abcde`, into its rule
+/// and password
+pub fn parse_line(line: &str) -> Result<(PasswordRule, &str), ParseError> {
+    // split it on the colon
+    let idx = line.find(':').ok_or_else(|| ParseError::Missing {
+        at: Location::end_of(line),
+        what: "\":\" after the rule".into(),
+    })?;
+    let rule = PasswordRule::parse_within(line, &line[..idx])?;
+    match line[idx + 1..].trim() {
+        "" => Err(ParseError::Missing {
+            at: Location::end_of(line),
+            what: "password after the :".into(),
+        }),
+        pw => Ok((rule, pw)),
+    }
+}
+
+/// Parse every line of the password file
+fn parse_input(input: &str) -> Result<Vec<(PasswordRule, &str)>, ParseError> {
+    parse_lines(input, parse_line)
+}
+
+/// Generate a password file of `count` lines like `1-3 a: abcde`
+///
+/// Passwords lean towards their rule's character, so that some of them pass
+/// each policy and some don't.
+pub fn generate_passwords<R: Rng>(count: usize, rng: &mut R) -> String {
+    let letter = |rng: &mut R| rng.gen_range(b'a'..=b'z') as char;
+    (0..count)
+        .map(|_| {
+            let character = letter(rng);
+            let low = rng.gen_range(1..10);
+            let high = rng.gen_range(low + 1..=low + 10);
+            let password = (0..rng.gen_range(high..=high + 5))
+                .map(|_| match rng.gen_bool(0.4) {
+                    true => character,
+                    false => letter(rng),
                 })
+                .collect::<String>();
+            format!("{}-{} {}: {}\n", low, high, character, password)
         })
-        // collect passwords into a vec of (rule, pw)
-        .collect::<Result<Vec<_>, anyhow::Error>>()
-        .map(|vals| {
-            // if that succeeded, iterate over values
-            vals.iter()
-                // return only those with valid password
-                .filter(|(pw_rule, pw)| pw_rule.is_valid_first_question(pw))
-                // count them
-                .count()
-        })
+        .collect()
+}
+
+fn part_one(input: &str) -> anyhow::Result<usize> {
+    Ok(parse_input(input)?
+        .iter()
+        // return only those with valid password
+        .filter(|(pw_rule, pw)| pw_rule.is_valid_first_question(pw))
+        // count them
+        .count())
+}
+
+fn part_two(input: &str) -> anyhow::Result<usize> {
+    Ok(parse_input(input)?
+        .iter()
+        .filter(|(pw_rule, pw)| pw_rule.is_valid_second_question(pw))
+        .count())
+}
+
+pub fn day_two_solution_one() -> anyhow::Result<usize> {
+    part_one(INPUT)
 }
 
-// yeah yeah this is just copied from the first one and literaly the only
-// change is calling pw_rule.is_valid_second_question instead of
-// pw_rule.is_valid_first_question, and I could probably simplify, but
-// why bother?
 pub fn day_two_solution_two() -> anyhow::Result<usize> {
-    INPUT
-        .lines()
-        // for each line
-        .map(|ln| {
-            // split it on the colon
-            let mut split = ln.split(':');
-            split
-                // grab the first item of the split
-                .next()
-                // remove leading and trailing whitespace
-                .map(str::trim)
-                // convert from option to result
-                .ok_or(anyhow::anyhow!("Input must contain a :"))
-                // attempt to convert to password rule
-                .and_then(|pw_rule| {
-                    PasswordRule::try_from(PasswordRuleWrapper(pw_rule))
-                })
-                // if we succeeded, chain to getting the password
-                .and_then(|pw_rule| {
-                    split
-                        // grab the second item out of the split
-                        .next()
-                        // trim it of whitespace
-                        .map(str::trim)
-                        // convert from option to result
-                        .ok_or(anyhow::anyhow!(
-                            "Input must contain a password after the :"
-                        ))
-                        // return a tuple of rule and password
-                        .map(|pw| (pw_rule, pw))
-                })
-        })
-        // collect passwords into a vec of (rule, pw)
-        .collect::<Result<Vec<_>, anyhow::Error>>()
-        .map(|vals| {
-            // if that succeeded, iterate over values
-            vals.iter()
-                // return only those with valid password
-                .filter(|(pw_rule, pw)| pw_rule.is_valid_second_question(pw))
-                // count them
-                .count()
-        })
+    part_two(INPUT)
+}
+
+/// Both parts, for the runner
+pub const SOLUTIONS: [Solution; 2] = [
+    Solution {
+        day: 2,
+        part: 1,
+        input: INPUT,
+        solve: part_one,
+        examples: &[Example {
+            input: EXAMPLE,
+            answer: 2,
+        }],
+    },
+    Solution {
+        day: 2,
+        part: 2,
+        input: INPUT,
+        solve: part_two,
+        examples: &[Example {
+            input: EXAMPLE,
+            answer: 1,
+        }],
+    },
+];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bad_lines_are_located() {
+        let input = "1-3 a: abcde\n1-3 b: cdefg\n2-x c: ccccc\n";
+        let err = parse_input(input).err().unwrap();
+        assert_eq!(err.to_string(), "Invalid number \"x\" at line 3, column 3");
+        let errors = ["1-3 a abcde", "1-3 ab: abc", "13 a: abc", "1-3 a:"]
+            .iter()
+            .map(|ln| parse_line(ln).err().unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            vec![
+                "Missing \":\" after the rule at line 1, column 12",
+                "Malformed character \"ab\" at line 1, column 5, expected a single \
+                 character",
+                "Malformed range \"13\" at line 1, column 1, expected two numbers \
+                 separated by a -",
+                "Missing password after the : at line 1, column 7",
+            ]
+        );
+    }
 }
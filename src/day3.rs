@@ -1,153 +1,167 @@
-static INPUT: &'static str = include_str!("day3.input");
+use anyhow::{anyhow, Error};
+use rand::Rng;
 
-/// A point on the map
-#[derive(Clone)]
-struct Coordinate {
-    x: usize,
-    y: usize,
-}
-impl Coordinate {
-    fn new(x: usize, y: usize) -> Self {
-        Coordinate { x, y }
-    }
-}
+use crate::error::ParseError;
+use crate::grid::{Coordinate, Grid, Vector, Wrap};
+use crate::runner::{Example, Solution};
 
-/// A direction of travel
-struct Vector {
-    delta_x: usize,
-    delta_y: usize,
-}
-impl Vector {
-    fn new(delta_x: usize, delta_y: usize) -> Self {
-        Vector { delta_x, delta_y }
-    }
-}
+const INPUT: &str = include_str!("day3.input");
+const EXAMPLE: &str = include_str!("day3.example");
 
 /// The contents of a point on the map
-enum CellContent {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellContent {
     Empty,
     Tree,
 }
-impl From<char> for CellContent {
-    fn from(c: char) -> Self {
+impl CellContent {
+    pub fn from_char(c: char) -> Option<Self> {
         match c {
-            '.' => Self::Empty,
-            '#' => Self::Tree,
-            _ => panic!("yikes! bad character!"),
+            '.' => Some(Self::Empty),
+            '#' => Some(Self::Tree),
+            _ => None,
+        }
+    }
+
+    pub fn as_char(self) -> char {
+        match self {
+            Self::Empty => '.',
+            Self::Tree => '#',
         }
     }
 }
 
-/// The map itself
-struct TreeMap {
-    // the map is a 2D vec of string refs to the input
-    map: Vec<Vec<char>>,
+/// The map itself, which repeats forever out to the right but not down
+pub struct TreeMap {
+    map: Grid<CellContent>,
 }
 impl TreeMap {
-    fn new(map: &'static str) -> Self {
-        // could we do validation of the map here? Yeah! Will we? No!
-        Self {
-            map: map
-                .lines()
-                .map(|ln| ln.trim())
-                .filter(|ln| ln != &"")
-                .map(|ln| ln.chars().collect::<Vec<char>>())
-                .collect(),
-        }
+    /// Parse a map of `.` and `#`, one row per line. Every row has to be the
+    /// same width, and there has to be at least one.
+    pub fn new(map: &str) -> Result<Self, ParseError> {
+        Ok(Self {
+            map: Grid::parse(map, CellContent::from_char, "'.' or '#'")?,
+        })
     }
-    fn char_at(&self, coord: &Coordinate) -> Option<char> {
-        // we'll say the coordinate grid starts at 0,0 (x, y) at the top left.
-        // so a 2-by-2 map would have a max coord of 1,1
-
-        // we assume the map is rectangular and has at least one column!
-        let map_width = self.map.get(0).expect("this map has no cols!").len();
-        let max_y_idx = self.map.len() - 1;
-
-        // we want the map to be infinite OUT TO THE RIGHT but not down.
-        // so if we're beyond the height, /shrug
-        if coord.y > max_y_idx {
-            return None;
-        }
-
-        // in any other case, we'll have something to return. If the x-coord
-        // is beyond the map width, we just need to divide it and return the
-        // modulo since the map repeats infinitely out to the right, so e.g. on
-        // a 2-by-2 map, the coord 2,0 would be equivalent to the coord 0,0,
-        // which 0 is 2 % 2.
-        let x_coord = coord.x % map_width;
 
-        Some(self.map[coord.y][x_coord])
+    pub fn char_at(&self, coord: &Coordinate) -> Option<char> {
+        self.content_at(coord).map(CellContent::as_char)
     }
 
     /// The content at a particular coordinate
-    fn content_at(&self, coord: &Coordinate) -> Option<CellContent> {
-        self.char_at(coord).map(|c| c.into())
+    pub fn content_at(&self, coord: &Coordinate) -> Option<CellContent> {
+        // e.g. on a 2-by-2 map, the coord 2,0 is equivalent to the coord 0,0
+        self.map
+            .wrapped(coord.x as isize, coord.y as isize, Wrap::Horizontal)
+            .map(|c| self.map[c])
     }
 
     /// An iterable of the content along a particular path, starting at start
-    fn content_along<'a>(
-        // we've got to specify that the vector reference lives as long as the
-        // map so the iterator can know the vec is good to keep a reference to
-        &'a self,
+    pub fn content_along(
+        &self,
         start: &Coordinate,
-        vector: &'a Vector,
-    ) -> MapTraverser {
-        MapTraverser::new(self, vector, start.clone())
+        vector: &Vector,
+    ) -> impl Iterator<Item = CellContent> + '_ {
+        self.map
+            .ray(*start, *vector, Wrap::Horizontal)
+            .map(|(_, content)| *content)
     }
 
     /// How many trees lie on a given path, starting in the top-left corner
-    fn trees_on_path<'a>(&'a self, vector: &'a Vector) -> usize {
+    pub fn trees_on_path(&self, vector: &Vector) -> usize {
         self.content_along(&Coordinate::new(0, 0), vector)
-            .filter_map(|c| match c {
-                CellContent::Tree => Some(c),
-                _ => None,
-            })
+            .filter(|c| *c == CellContent::Tree)
             .count()
     }
 }
 
-struct MapTraverser<'a> {
-    map: &'a TreeMap,
-    vector: &'a Vector,
-    coord: Coordinate,
-}
-impl<'a> MapTraverser<'a> {
-    fn new(map: &'a TreeMap, vector: &'a Vector, start: Coordinate) -> Self {
-        Self {
-            map,
-            vector,
-            coord: start,
-        }
-    }
-}
-impl<'a> Iterator for MapTraverser<'a> {
-    type Item = CellContent;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        match self.map.content_at(&self.coord) {
-            Some(c) => {
-                self.coord = Coordinate::new(
-                    self.coord.x + self.vector.delta_x,
-                    self.coord.y + self.vector.delta_y,
-                );
-                Some(c)
-            }
-            None => None,
-        }
+/// Generate a `width` by `height` map where each cell has a tree with
+/// probability `density`
+pub fn generate_forest<R: Rng>(
+    width: usize,
+    height: usize,
+    density: f64,
+    rng: &mut R,
+) -> Result<String, Error> {
+    if width == 0 || height == 0 || !(0.0..=1.0).contains(&density) {
+        return Err(anyhow!(
+            "Can't grow a {}x{} forest with a tree density of {}",
+            width,
+            height,
+            density
+        ));
     }
+    let forest = Grid::from_fn(width, height, |_| match rng.gen_bool(density) {
+        true => CellContent::Tree,
+        false => CellContent::Empty,
+    });
+    Ok(forest.render(|c| c.as_char()))
 }
 
-pub fn day_three_solution_one() -> usize {
-    TreeMap::new(INPUT).trees_on_path(&Vector::new(3, 1))
+fn part_one(input: &str) -> Result<usize, Error> {
+    Ok(TreeMap::new(input)?.trees_on_path(&Vector::new(3, 1)))
 }
 
-pub fn day_three_solution_two() -> usize {
+fn part_two(input: &str) -> Result<usize, Error> {
     // we're in good shape here b/c it just wants us to multiply results from
     // different vectors.
-    let map = TreeMap::new(INPUT);
-    map.trees_on_path(&Vector::new(1, 1))
+    let map = TreeMap::new(input)?;
+    Ok(map.trees_on_path(&Vector::new(1, 1))
         * map.trees_on_path(&Vector::new(3, 1))
         * map.trees_on_path(&Vector::new(5, 1))
         * map.trees_on_path(&Vector::new(7, 1))
-        * map.trees_on_path(&Vector::new(1, 2))
+        * map.trees_on_path(&Vector::new(1, 2)))
+}
+
+pub fn day_three_solution_one() -> Result<usize, Error> {
+    part_one(INPUT)
+}
+
+pub fn day_three_solution_two() -> Result<usize, Error> {
+    part_two(INPUT)
+}
+
+/// Both parts, for the runner
+pub const SOLUTIONS: [Solution; 2] = [
+    Solution {
+        day: 3,
+        part: 1,
+        input: INPUT,
+        solve: part_one,
+        examples: &[Example {
+            input: EXAMPLE,
+            answer: 7,
+        }],
+    },
+    Solution {
+        day: 3,
+        part: 2,
+        input: INPUT,
+        solve: part_two,
+        examples: &[Example {
+            input: EXAMPLE,
+            answer: 336,
+        }],
+    },
+];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bad_maps_are_rejected() {
+        let map = TreeMap::new("..#\n#..\n").unwrap();
+        assert_eq!(map.trees_on_path(&Vector::new(1, 1)), 0);
+        assert_eq!(map.char_at(&Coordinate::new(5, 0)), Some('#'));
+        assert_eq!(
+            TreeMap::new("..#\n#.x\n").err().unwrap().to_string(),
+            "Invalid character \"x\" at line 2, column 3, expected '.' or '#'"
+        );
+        assert_eq!(
+            TreeMap::new("..#\n#.\n").err().unwrap().to_string(),
+            "Malformed row \"#.\" at line 2, column 1, expected 3 columns"
+        );
+        assert!(TreeMap::new("\n").is_err());
+    }
 }
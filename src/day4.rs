@@ -1,12 +1,29 @@
 use anyhow::{anyhow, Error};
-use lazy_static::lazy_static;
-use regex::Regex;
-use std::{collections::HashMap, convert::TryFrom};
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, convert::TryFrom, fmt};
 
-static INPUT: &'static str = include_str!("day4.input");
+use crate::error::{Location, ParseError};
+use crate::input::{key_values, records, Field, ParseMode};
+use crate::runner::{Example, Solution};
 
-#[derive(Debug)]
-enum EyeColor {
+pub mod fields;
+pub mod formats;
+pub mod height;
+pub mod repair;
+pub mod rules;
+
+use fields::{PassportId, RgbColor, Year};
+use height::Height;
+use rules::{FieldRule, Schema};
+
+const INPUT: &str = include_str!("day4.input");
+const EXAMPLE: &str = include_str!("day4.example");
+const INVALID_EXAMPLE: &str = include_str!("day4.example.invalid");
+const VALID_EXAMPLE: &str = include_str!("day4.example.valid");
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EyeColor {
     Amber,
     Blue,
     Brown,
@@ -14,6 +31,8 @@ enum EyeColor {
     Green,
     Hazel,
     Other,
+    /// A color that isn't one of the puzzle's, but that a schema allows
+    Custom(String),
 }
 impl TryFrom<&str> for EyeColor {
     type Error = Error;
@@ -31,236 +50,646 @@ impl TryFrom<&str> for EyeColor {
         }
     }
 }
-
-#[derive(Debug)]
-enum HeightUnit {
-    Centimeters,
-    Inches,
+impl fmt::Display for EyeColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            EyeColor::Amber => "amb",
+            EyeColor::Blue => "blu",
+            EyeColor::Brown => "brn",
+            EyeColor::Gray => "gry",
+            EyeColor::Green => "grn",
+            EyeColor::Hazel => "hzl",
+            EyeColor::Other => "oth",
+            EyeColor::Custom(color) => color,
+        })
+    }
 }
 
-#[derive(Debug)]
-struct Height {
-    number: usize,
-    unit: HeightUnit,
+// Valid passports are written out as the passports they came from, and have
+// to pass validation again when they're read back in.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "Passport", into = "Passport")]
+pub struct ValidPassport {
+    // only the fields the schema requires are sure to be here
+    birth_year: Option<Year>,
+    issue_year: Option<Year>,
+    expiration_year: Option<Year>,
+    height: Option<Height>,
+    hair_color: Option<RgbColor>,
+    eye_color: Option<EyeColor>,
+    passport_id: Option<PassportId>,
+    country_id: Option<String>,
 }
-impl TryFrom<&str> for Height {
-    type Error = Error;
+impl ValidPassport {
+    /// Validate a passport against a set of rules, rather than the built-in
+    /// passport rules
+    ///
+    /// The schema decides which fields are required, and for fields limited
+    /// to a set of values, what those values are. Every field that's there
+    /// still has to be a year, height, color, eye color or passport ID to be
+    /// stored here, and any that isn't is reported as invalid. Only the keys
+    /// in `Passport::KNOWN_KEYS` are ever checked, so a schema that requires
+    /// any other key rejects every passport.
+    pub fn with_schema(value: Passport, schema: &Schema) -> Result<Self, ParseError> {
+        schema.validate(&value.fields(), |key| value.location(key))?;
+        let invalid = |key: &str, what: &str| ParseError::Invalid {
+            at: value.location(key),
+            what: what.into(),
+        };
+        // the schema has already checked eye colors against its own values
+        let allowed_colors = schema.field("ecl").and_then(FieldRule::allowed_values);
+        let eye_color = |color: &str| {
+            EyeColor::try_from(color).ok().or_else(|| {
+                let color = color.trim();
+                allowed_colors?
+                    .iter()
+                    .any(|allowed| allowed == color)
+                    .then(|| EyeColor::Custom(color.to_owned()))
+            })
+        };
+        Ok(Self {
+            birth_year: typed(&value.birth_year, |v| v.parse().ok())
+                .ok_or_else(|| invalid("byr", "birth year"))?,
+            issue_year: typed(&value.issue_year, |v| v.parse().ok())
+                .ok_or_else(|| invalid("iyr", "issue year"))?,
+            expiration_year: typed(&value.expiration_year, |v| v.parse().ok())
+                .ok_or_else(|| invalid("eyr", "expiration year"))?,
+            height: typed(&value.height, |v| Height::try_from(v).ok())
+                .ok_or_else(|| invalid("hgt", "height"))?,
+            hair_color: typed(&value.hair_color, |v| v.parse().ok())
+                .ok_or_else(|| invalid("hcl", "hair color"))?,
+            eye_color: typed(&value.eye_color, eye_color)
+                .ok_or_else(|| invalid("ecl", "eye color"))?,
+            passport_id: typed(&value.passport_id, |v| v.parse().ok())
+                .ok_or_else(|| invalid("pid", "passport ID"))?,
+            country_id: value.country_id,
+        })
+    }
 
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        let value = value.trim();
-
-        // Parse the unit. Input specifies case-sensitvity
-        let unit = if value.ends_with("cm") {
-            Some(HeightUnit::Centimeters)
-        } else if value.ends_with("in") {
-            Some(HeightUnit::Inches)
-        } else {
-            None
-        }
-        .ok_or(anyhow!("Value {} does not have a unit", value))?;
-
-        // grab the numeric part of the string and convert it to a usize
-        let number = value
-            .get(0..value.len() - 2)
-            // if the .get() failed we're missing a number in front of the unit
-            .ok_or(anyhow!(
-                "Value {} does not contain a number and a unit",
-                value
-            ))?
-            .parse::<usize>()
-            .map_err(Error::from)?;
-
-        // check for valid height for a given unit
-        match unit {
-            HeightUnit::Centimeters => match number {
-                150..=193 => Ok(Self { number, unit }),
-                _ => Err(anyhow!("Height {:?} is too large or too small", number)),
-            },
-            HeightUnit::Inches => match number {
-                59..=76 => Ok(Self { number, unit }),
-                _ => Err(anyhow!("Height {:?} is too large or too small", number)),
-            },
-        }
+    pub fn birth_year(&self) -> Option<Year> {
+        self.birth_year
     }
-}
 
-#[derive(Debug)]
-struct ValidPassport {
-    birth_year: usize,
-    issue_year: usize,
-    expiration_year: usize,
-    height: Height,
-    hair_color: String,
-    eye_color: EyeColor,
-    passport_id: String,
-    country_id: Option<String>,
+    pub fn issue_year(&self) -> Option<Year> {
+        self.issue_year
+    }
+
+    pub fn expiration_year(&self) -> Option<Year> {
+        self.expiration_year
+    }
+
+    pub fn height(&self) -> Option<Height> {
+        self.height
+    }
+
+    pub fn hair_color(&self) -> Option<RgbColor> {
+        self.hair_color
+    }
+
+    pub fn eye_color(&self) -> Option<&EyeColor> {
+        self.eye_color.as_ref()
+    }
+
+    pub fn passport_id(&self) -> Option<&PassportId> {
+        self.passport_id.as_ref()
+    }
+
+    pub fn country_id(&self) -> Option<&str> {
+        self.country_id.as_deref()
+    }
+}
+/// Convert a field that may be missing, giving `None` if it's there but
+/// doesn't convert
+fn typed<T, F>(value: &Option<String>, convert: F) -> Option<Option<T>>
+where
+    F: Fn(&str) -> Option<T>,
+{
+    match value {
+        Some(value) => convert(value).map(Some),
+        None => Some(None),
+    }
 }
+
 impl TryFrom<Passport> for ValidPassport {
-    type Error = Error;
+    type Error = ParseError;
 
     fn try_from(value: Passport) -> Result<Self, Self::Error> {
-        lazy_static! {
-            static ref HEIGHT_RE: Regex = Regex::new(r"^#[\da-f]{6}$").unwrap();
-            static ref PASSPORT_RE: Regex = Regex::new(r"^\d{9}$").unwrap();
+        Self::with_schema(value, Schema::passport())
+    }
+}
+impl From<ValidPassport> for Passport {
+    fn from(value: ValidPassport) -> Self {
+        Self {
+            birth_year: value.birth_year.map(|v| v.to_string()),
+            issue_year: value.issue_year.map(|v| v.to_string()),
+            expiration_year: value.expiration_year.map(|v| v.to_string()),
+            height: value.height.map(|v| v.to_string()),
+            hair_color: value.hair_color.map(|v| v.to_string()),
+            eye_color: value.eye_color.map(|v| v.to_string()),
+            passport_id: value.passport_id.map(|v| v.to_string()),
+            country_id: value.country_id,
+            warnings: Vec::new(),
+            locations: SourceLocations::default(),
+        }
+    }
+}
+
+/// Something questionable that was accepted when parsing in lenient mode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseWarning {
+    /// A key appeared more than once. The value is the one that was dropped
+    /// in favor of the later one.
+    DuplicateField { key: String, value: String },
+    /// A key that isn't part of a passport, which is ignored
+    UnknownField { key: String, value: String },
+    /// A value with a `:` in it, which is kept in full
+    ExtraSeparator { key: String, value: String },
+}
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseWarning::DuplicateField { key, value } => {
+                write!(f, "Duplicate field {}, dropped value {}", key, value)
+            }
+            ParseWarning::UnknownField { key, value } => {
+                write!(f, "Unknown field {}:{}", key, value)
+            }
+            ParseWarning::ExtraSeparator { key, value } => {
+                write!(f, "Value for field {} contains a ':': {}", key, value)
+            }
         }
-        Ok(Self {
-            birth_year: value
-                .birth_year
-                .parse::<usize>()
-                .map_err(Error::from)
-                .and_then(|i| match i {
-                    1920..=2002 => Ok(i),
-                    _ => Err(anyhow!("Invalid birth year {}", i)),
-                })?,
-            issue_year: value
-                .issue_year
-                .parse::<usize>()
-                .map_err(Error::from)
-                .and_then(|i| match i {
-                    2010..=2020 => Ok(i),
-                    _ => Err(anyhow!("Invalid issue year {}", i)),
-                })?,
-            expiration_year: value
-                .expiration_year
-                .parse::<usize>()
-                .map_err(Error::from)
-                .and_then(|i| match i {
-                    2020..=2030 => Ok(i),
-                    _ => Err(anyhow!("Invalid expiration year {}", i)),
-                })?,
-            height: Height::try_from(value.height.as_ref())?,
-            hair_color: Ok(value.hair_color.trim()).and_then(
-                |string| match HEIGHT_RE.is_match(string) {
-                    true => Ok(string.to_owned()),
-                    false => Err(anyhow!("Invalid hair color {}", string)),
-                },
-            )?,
-            eye_color: EyeColor::try_from(value.eye_color.as_ref())?,
-            passport_id: Ok(value.passport_id.trim()).and_then(|string| {
-                match PASSPORT_RE.is_match(string) {
-                    true => Ok(string.to_owned()),
-                    false => Err(anyhow!("Invalid passport ID {}", string)),
-                }
-            })?,
-            country_id: value.country_id.clone(),
-        })
     }
 }
 
-#[derive(Debug)]
-struct Passport {
+/// Where a passport's values were in the text it was parsed from
+#[derive(Debug, Clone, Default)]
+struct SourceLocations {
+    fields: HashMap<String, Location>,
+    /// Just after the passport, where any missing fields would have been
+    end: Option<Location>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Passport {
     // Storing everything as a string for the moment because the question doesn't
-    // imply anything about valid data, only the presence or absence of keys
-    birth_year: String,
-    issue_year: String,
-    expiration_year: String,
-    height: String,
-    hair_color: String,
-    eye_color: String,
-    passport_id: String,
+    // imply anything about valid data, only the presence or absence of keys.
+    // Which keys have to be present is up to the schema.
+    birth_year: Option<String>,
+    issue_year: Option<String>,
+    expiration_year: Option<String>,
+    height: Option<String>,
+    hair_color: Option<String>,
+    eye_color: Option<String>,
+    passport_id: Option<String>,
     country_id: Option<String>,
+    #[serde(skip)]
+    warnings: Vec<ParseWarning>,
+    #[serde(skip)]
+    locations: SourceLocations,
 }
-impl TryFrom<&HashMap<&str, &str>> for Passport {
-    type Error = Error;
+impl Passport {
+    pub const KNOWN_KEYS: [&'static str; 8] =
+        ["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid", "cid"];
 
-    fn try_from(value: &HashMap<&str, &str>) -> Result<Self, Self::Error> {
-        Ok(Self {
-            birth_year: value
-                .get("byr")
-                .map(|i| String::from(*i))
-                .ok_or(anyhow!("no birth_year"))?,
-            issue_year: value
-                .get("iyr")
-                .map(|i| String::from(*i))
-                .ok_or(anyhow!("no issue_year"))?,
-            expiration_year: value
-                .get("eyr")
-                .map(|i| String::from(*i))
-                .ok_or(anyhow!("no expiration_year"))?,
-            height: value
-                .get("hgt")
-                .map(|i| String::from(*i))
-                .ok_or(anyhow!("no height"))?,
-            hair_color: value
-                .get("hcl")
-                .map(|i| String::from(*i))
-                .ok_or(anyhow!("no hair_color"))?,
-            eye_color: value
-                .get("ecl")
-                .map(|i| String::from(*i))
-                .ok_or(anyhow!("no eye_color"))?,
-            passport_id: value
-                .get("pid")
-                .map(|i| String::from(*i))
-                .ok_or(anyhow!("no passport_id"))?,
-            country_id: value.get("cid").map(|i| String::from(*i)),
-        })
+    /// Parse a passport entry, treating duplicate and unknown keys and values
+    /// containing `:` as errors in strict mode, or as warnings in lenient mode
+    pub fn parse(value: &str, mode: ParseMode) -> Result<Self, ParseError> {
+        Self::parse_with_schema(value, mode, Schema::passport())
     }
-}
-impl TryFrom<&str> for Passport {
-    type Error = Error;
 
-    /// Convert a passport entry into
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
+    /// Parse a passport entry like `parse`, with the fields that the schema
+    /// requires, rather than the built-in passport rules
+    pub fn parse_with_schema(
+        value: &str,
+        mode: ParseMode,
+        schema: &Schema,
+    ) -> Result<Self, ParseError> {
         // A passport entry looks like:
         // cid:124 byr:1935 eyr:2020 ecl:blu
         // hcl:#a97842 pid:666776663 iyr:2010
         // hgt:68in
-        value
-            // split on spaces or newlines
-            .split_whitespace()
-            // split each item into a k, v pair on :
-            // returns Result<(k, v), Error>, where the error is in case there's
-            // not two items in the split.
-            .map(|v| {
-                let iter = v.split(':');
-                let parts = iter.take(2).collect::<Vec<&str>>();
-                match parts[..] {
-                    [i, j] => Ok((i, j)),
-                    _ => Err(anyhow!(dbg!(v.to_owned()))),
-                }
-            })
-            // fold over the results, short-circuiting if any of them were an
-            // error, or inserting the result into a hashmap otherwise.
-            .try_fold(HashMap::new(), |mut items, res| {
-                let (k, v) = res?;
-                items.insert(k, v);
-                Ok(items)
+        let mut warnings = Vec::new();
+        let mut warn = |warning: ParseWarning, error: ParseError| match mode {
+            ParseMode::Strict => Err(error),
+            ParseMode::Lenient => {
+                warnings.push(warning);
+                Ok(())
+            }
+        };
+        let mut items = HashMap::new();
+        let mut locations = SourceLocations {
+            fields: HashMap::new(),
+            end: Some(Location::end_of(value)),
+        };
+        for field in key_values(value) {
+            // each item is split into a k, v pair on the first :
+            let Field {
+                key: k,
+                value: v,
+                token,
+            } = field?;
+            if v.contains(':') {
+                warn(
+                    ParseWarning::ExtraSeparator {
+                        key: k.to_owned(),
+                        value: v.to_owned(),
+                    },
+                    ParseError::Malformed {
+                        at: Location::of(value, token),
+                        what: "field".into(),
+                        expected: "a single : in key:value".into(),
+                    },
+                )?;
+            }
+            if !Self::KNOWN_KEYS.contains(&k) {
+                warn(
+                    ParseWarning::UnknownField {
+                        key: k.to_owned(),
+                        value: v.to_owned(),
+                    },
+                    ParseError::Invalid {
+                        at: Location::of(value, k),
+                        what: "field".into(),
+                    },
+                )?;
+                continue;
+            }
+            // later values win, as they always have
+            locations
+                .fields
+                .insert(k.to_owned(), Location::of(value, v));
+            if let Some(prev) = items.insert(k, v) {
+                warn(
+                    ParseWarning::DuplicateField {
+                        key: k.to_owned(),
+                        value: prev.to_owned(),
+                    },
+                    ParseError::Invalid {
+                        at: Location::of(value, token),
+                        what: "duplicate field".into(),
+                    },
+                )?;
+            }
+        }
+        let field = |key: &str| items.get(key).map(|v| String::from(*v));
+        let passport = Self {
+            birth_year: field("byr"),
+            issue_year: field("iyr"),
+            expiration_year: field("eyr"),
+            height: field("hgt"),
+            hair_color: field("hcl"),
+            eye_color: field("ecl"),
+            passport_id: field("pid"),
+            country_id: field("cid"),
+            warnings,
+            locations,
+        };
+        match passport.missing_field(schema) {
+            Some(key) => Err(ParseError::Missing {
+                at: Location::end_of(value),
+                what: format!("field {}", key),
+            }),
+            None => Ok(passport),
+        }
+    }
+
+    /// The first of the passport's keys that the schema requires but that
+    /// the passport doesn't have
+    pub fn missing_field(&self, schema: &Schema) -> Option<&'static str> {
+        let fields = self.fields();
+        Self::KNOWN_KEYS.iter().copied().find(|key| {
+            schema.field(key).is_some_and(FieldRule::required)
+                && !fields.contains_key(key)
+        })
+    }
+
+    /// Where the value for `key` was in the text the passport was parsed
+    /// from, or where it should have been if it was missing
+    ///
+    /// Passports that weren't parsed from text, or whose value has been
+    /// changed since, can only locate the value within itself.
+    pub fn location(&self, key: &str) -> Location {
+        self.locations
+            .fields
+            .get(key)
+            .cloned()
+            .or_else(|| {
+                self.fields()
+                    .get(key)
+                    .map(|value| Location::of(value, value))
             })
-            .and_then(|items| Passport::try_from(&items))
+            .or_else(|| self.locations.end.clone())
+            .unwrap_or_else(|| Location::end_of(""))
+    }
+
+    /// Move the passport's locations down by the given number of lines, for
+    /// when it was parsed from one record of a larger input
+    fn offset_lines(mut self, lines: usize) -> Self {
+        let locations = &mut self.locations;
+        locations
+            .fields
+            .values_mut()
+            .chain(locations.end.as_mut())
+            .for_each(|at| at.line += lines);
+        self
+    }
+
+    /// Anything questionable that was accepted when this was parsed
+    pub fn warnings(&self) -> &[ParseWarning] {
+        &self.warnings
+    }
+
+    /// Set the value of the field with the given key
+    pub fn set_field(&mut self, key: &str, value: String) -> Result<(), Error> {
+        let field = match key {
+            "byr" => &mut self.birth_year,
+            "iyr" => &mut self.issue_year,
+            "eyr" => &mut self.expiration_year,
+            "hgt" => &mut self.height,
+            "hcl" => &mut self.hair_color,
+            "ecl" => &mut self.eye_color,
+            "pid" => &mut self.passport_id,
+            "cid" => &mut self.country_id,
+            _ => return Err(anyhow!("Unknown field {}", key)),
+        };
+        *field = Some(value);
+        self.locations.fields.remove(key);
+        Ok(())
+    }
+
+    /// The passport's fields, keyed as they are in the batch file
+    pub fn fields(&self) -> HashMap<&'static str, &str> {
+        [
+            ("byr", &self.birth_year),
+            ("iyr", &self.issue_year),
+            ("eyr", &self.expiration_year),
+            ("hgt", &self.height),
+            ("hcl", &self.hair_color),
+            ("ecl", &self.eye_color),
+            ("pid", &self.passport_id),
+            ("cid", &self.country_id),
+        ]
+        .iter()
+        .filter_map(|(key, value)| Some((*key, value.as_deref()?)))
+        .collect()
+    }
+}
+// Warnings are about how a passport was written down, not what's in it, so
+// they don't count towards equality.
+impl PartialEq for Passport {
+    fn eq(&self, other: &Self) -> bool {
+        self.fields() == other.fields()
     }
 }
+impl TryFrom<&str> for Passport {
+    type Error = ParseError;
 
-pub fn day_four_solution_one() -> usize {
-    INPUT
-        .split("\n\n")
-        .map(Passport::try_from)
+    /// Convert a passport entry into a passport, leniently
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Passport::parse(value, ParseMode::Lenient)
+    }
+}
+
+/// Generate a batch of `count` passports in the puzzle's format
+///
+/// About half of them are valid. Of the rest, some are missing a required
+/// field and some have a value that fails validation. Fields come in any
+/// order, spread over one or more lines.
+pub fn generate_passports<R: Rng>(count: usize, rng: &mut R) -> String {
+    const EYE_COLORS: [&str; 7] = ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"];
+    let passports = (0..count).map(|_| {
+        let height = match rng.gen_bool(0.5) {
+            true => format!("{}cm", rng.gen_range(150..=193)),
+            false => format!("{}in", rng.gen_range(59..=76)),
+        };
+        let mut fields = vec![
+            ("byr", rng.gen_range(1920..=2002).to_string()),
+            ("iyr", rng.gen_range(2010..=2020).to_string()),
+            ("eyr", rng.gen_range(2020..=2030).to_string()),
+            ("hgt", height),
+            ("hcl", format!("#{:06x}", rng.gen_range(0..0x100_0000))),
+            ("ecl", EYE_COLORS.choose(rng).unwrap_or(&"oth").to_string()),
+            ("pid", format!("{:09}", rng.gen_range(0..1_000_000_000))),
+        ];
+        if rng.gen_bool(0.5) {
+            fields.push(("cid", rng.gen_range(1..1000).to_string()));
+        }
+        let idx = rng.gen_range(0..7);
+        match rng.gen_range(0..4) {
+            0 => {
+                fields.remove(idx);
+            }
+            1 => {
+                let (key, value) = &mut fields[idx];
+                *value = match *key {
+                    "byr" => "2003".into(),
+                    "iyr" => "2009".into(),
+                    "eyr" => "2031".into(),
+                    "hgt" => "190in".into(),
+                    "hcl" => "123abc".into(),
+                    "ecl" => "wat".into(),
+                    _ => "0123456789".into(),
+                }
+            }
+            _ => {}
+        }
+        fields.shuffle(rng);
+        fields
+            .iter()
+            .enumerate()
+            .map(|(idx, (key, value))| {
+                let separator = match idx {
+                    0 => "",
+                    _ if rng.gen_bool(0.25) => "\n",
+                    _ => " ",
+                };
+                format!("{}{}:{}", separator, key, value)
+            })
+            .collect::<String>()
+    });
+    passports.collect::<Vec<_>>().join("\n\n") + "\n"
+}
+
+fn part_one(input: &str) -> usize {
+    records(input)
+        .map(|record| Passport::try_from(record.text))
         .filter(|r| r.is_ok())
         .count()
 }
 
-pub fn day_four_solution_two() -> usize {
-    INPUT
-        .split("\n\n")
-        .map(|i| Passport::try_from(i).and_then(ValidPassport::try_from))
+fn part_two(input: &str) -> usize {
+    records(input)
+        .map(|record| Passport::try_from(record.text).and_then(ValidPassport::try_from))
         .filter(Result::is_ok)
         .count()
 }
 
+pub fn day_four_solution_one() -> usize {
+    part_one(INPUT)
+}
+
+pub fn day_four_solution_two() -> usize {
+    part_two(INPUT)
+}
+
+/// Both parts, for the runner
+pub const SOLUTIONS: [Solution; 2] = [
+    Solution {
+        day: 4,
+        part: 1,
+        input: INPUT,
+        solve: |input| Ok(part_one(input)),
+        examples: &[Example {
+            input: EXAMPLE,
+            answer: 2,
+        }],
+    },
+    Solution {
+        day: 4,
+        part: 2,
+        input: INPUT,
+        solve: |input| Ok(part_two(input)),
+        examples: &[
+            Example {
+                input: INVALID_EXAMPLE,
+                answer: 0,
+            },
+            Example {
+                input: VALID_EXAMPLE,
+                answer: 4,
+            },
+        ],
+    },
+];
+
 #[cfg(test)]
 mod test {
     use super::*;
-    static BAD_INPUT: &'static str = include_str!("day4.input.bad");
+    use formats::BatchWriter;
+    static BAD_INPUT: &str = include_str!("day4.input.bad");
 
     #[test]
     fn bad_input_is_bad() {
         assert_eq!(
-            BAD_INPUT
-                .split("\n\n")
-                .map(|i| { Passport::try_from(i).and_then(ValidPassport::try_from) })
+            records(BAD_INPUT)
+                .map(|r| Passport::try_from(r.text).and_then(ValidPassport::try_from))
                 .filter(Result::is_ok)
                 .count(),
             0
         )
     }
+
+    #[test]
+    fn invalid_values_are_located() {
+        let input =
+            "byr:1937 iyr:2017 eyr:2020 hgt:183cm\nhcl:#fffffd ecl:wat pid:860033327";
+        let passport = Passport::try_from(input).unwrap();
+        assert_eq!(
+            ValidPassport::try_from(passport.clone())
+                .unwrap_err()
+                .to_string(),
+            "Invalid eye color \"wat\" at line 2, column 17"
+        );
+        // the schema allows any eye color, but it still has to be one
+        let loose = Schema::from_toml("[fields.ecl]\ntype = \"any\"").unwrap();
+        assert_eq!(
+            ValidPassport::with_schema(passport.clone(), &loose)
+                .unwrap_err()
+                .to_string(),
+            "Invalid eye color \"wat\" at line 2, column 17"
+        );
+        let schema =
+            Schema::from_toml("[fields.cid]\nname = \"country ID\"\ntype = \"any\"")
+                .unwrap();
+        assert_eq!(
+            ValidPassport::with_schema(passport, &schema)
+                .unwrap_err()
+                .to_string(),
+            "Missing country ID at line 2, column 34"
+        );
+    }
+
+    #[test]
+    fn schemas_decide_required_fields_and_values() {
+        let schema = Schema::from_toml(
+            r#"
+            [fields.byr]
+            type = "integer"
+            range = [1920, 2002]
+            required = false
+
+            [fields.ecl]
+            type = "enum"
+            values = ["brn", "pnk"]
+
+            [fields.pid]
+            type = "pattern"
+            pattern = '^\d{9}$'
+            "#,
+        )
+        .unwrap();
+        let parse =
+            |input| Passport::parse_with_schema(input, ParseMode::Strict, &schema);
+        let input = "ecl:pnk pid:000000001";
+        assert!(Passport::parse(input, ParseMode::Strict).is_err());
+        let valid = ValidPassport::with_schema(parse(input).unwrap(), &schema).unwrap();
+        assert_eq!(valid.eye_color(), Some(&EyeColor::Custom("pnk".into())));
+        assert_eq!(valid.birth_year(), None);
+        assert_eq!(
+            BatchWriter::default().write(&valid.into()).unwrap(),
+            "ecl:pnk pid:000000001"
+        );
+        assert_eq!(
+            ValidPassport::with_schema(
+                parse("ecl:gry pid:000000001").unwrap(),
+                &schema
+            )
+            .unwrap_err()
+            .to_string(),
+            "Invalid ecl \"gry\" at line 1, column 5"
+        );
+        assert_eq!(
+            parse("pid:000000001").unwrap_err().to_string(),
+            "Missing field ecl at line 1, column 14"
+        );
+    }
+
+    #[test]
+    fn strict_and_lenient_parsing() {
+        let input = "byr:1937 iyr:2017 eyr:2020 hgt:183cm hcl:#fffffd ecl:gry
+            pid:860033327 byr:1938 xyz:1 cid:1:2";
+        assert_eq!(
+            Passport::parse(input, ParseMode::Strict)
+                .err()
+                .unwrap()
+                .to_string(),
+            "Invalid duplicate field \"byr:1938\" at line 2, column 27"
+        );
+        let passport = Passport::parse(input, ParseMode::Lenient).unwrap();
+        assert_eq!(passport.birth_year.as_deref(), Some("1938"));
+        assert_eq!(passport.country_id.as_deref(), Some("1:2"));
+        assert_eq!(
+            passport.warnings,
+            vec![
+                ParseWarning::DuplicateField {
+                    key: "byr".into(),
+                    value: "1937".into()
+                },
+                ParseWarning::UnknownField {
+                    key: "xyz".into(),
+                    value: "1".into()
+                },
+                ParseWarning::ExtraSeparator {
+                    key: "cid".into(),
+                    value: "1:2".into()
+                },
+            ]
+        );
+        let clean =
+            "byr:1937 iyr:2017 eyr:2020 hgt:183cm hcl:#fffffd ecl:gry pid:860033327";
+        assert!(Passport::parse(clean, ParseMode::Strict)
+            .unwrap()
+            .warnings
+            .is_empty());
+    }
 }
@@ -0,0 +1,153 @@
+use std::{fmt, str::FromStr};
+
+use crate::error::{Location, ParseError};
+
+/// An error for a value that couldn't be parsed as `what`, located at the
+/// value within `s`
+fn invalid(s: &str, what: &str) -> ParseError {
+    ParseError::Invalid {
+        at: Location::of(s, s.trim()),
+        what: what.into(),
+    }
+}
+
+/// A calendar year
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Year(u16);
+impl Year {
+    pub const fn new(year: u16) -> Self {
+        Year(year)
+    }
+
+    pub fn get(self) -> u16 {
+        self.0
+    }
+
+    /// The number of years from `earlier` to this year, which is negative
+    /// if `earlier` is actually later
+    pub fn years_since(self, earlier: Year) -> i32 {
+        i32::from(self.0) - i32::from(earlier.0)
+    }
+}
+impl FromStr for Year {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let year = s.trim();
+        // years are always written out in full, so don't accept e.g. "+2020"
+        match year.len() == 4 && year.chars().all(|c| c.is_ascii_digit()) {
+            true => year.parse().map(Year).map_err(|_| invalid(s, "year")),
+            false => Err(invalid(s, "year")),
+        }
+    }
+}
+impl fmt::Display for Year {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}", self.0)
+    }
+}
+
+/// A color, written as `#rrggbb`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RgbColor {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+impl RgbColor {
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        RgbColor { r, g, b }
+    }
+}
+impl FromStr for RgbColor {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s
+            .trim()
+            .strip_prefix('#')
+            .ok_or_else(|| invalid(s, "color"))?;
+        // checking for ASCII first means the byte slicing below is safe
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(invalid(s, "color"));
+        }
+        let channel = |idx: usize| {
+            u8::from_str_radix(&hex[idx..idx + 2], 16)
+                .expect("the channel is two hex digits")
+        };
+        Ok(RgbColor::new(channel(0), channel(2), channel(4)))
+    }
+}
+impl fmt::Display for RgbColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+/// A passport ID, which is a string of digits
+///
+/// This is kept as the original string rather than a number, since leading
+/// zeros are significant.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PassportId(String);
+impl PassportId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+impl FromStr for PassportId {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let id = s.trim();
+        match !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()) {
+            true => Ok(PassportId(id.to_owned())),
+            false => Err(invalid(s, "passport ID")),
+        }
+    }
+}
+impl fmt::Display for PassportId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fields_round_trip_through_strings() {
+        ["1920", "2020", "0999"].iter().for_each(|s| {
+            assert_eq!(s.parse::<Year>().unwrap().to_string(), *s);
+        });
+        ["#000000", "#123abc", "#ffffff"].iter().for_each(|s| {
+            assert_eq!(s.parse::<RgbColor>().unwrap().to_string(), *s);
+        });
+        ["000000001", "123456789"].iter().for_each(|s| {
+            assert_eq!(s.parse::<PassportId>().unwrap().to_string(), *s);
+        });
+    }
+
+    #[test]
+    fn fields_reject_bad_strings() {
+        assert!("20202".parse::<Year>().is_err());
+        assert!("+202".parse::<Year>().is_err());
+        assert!("123abc".parse::<RgbColor>().is_err());
+        assert!("#123abz".parse::<RgbColor>().is_err());
+        assert!("#12345é".parse::<RgbColor>().is_err());
+        assert!("12345678a".parse::<PassportId>().is_err());
+        assert!("".parse::<PassportId>().is_err());
+        assert_eq!(
+            " #12345".parse::<RgbColor>().unwrap_err().to_string(),
+            "Invalid color \"#12345\" at line 1, column 2"
+        );
+    }
+
+    #[test]
+    fn color_channels() {
+        let color = "#a97842".parse::<RgbColor>().unwrap();
+        assert_eq!((color.r, color.g, color.b), (0xa9, 0x78, 0x42));
+        assert_eq!(Year::new(2020).years_since(Year::new(1935)), 85);
+    }
+}
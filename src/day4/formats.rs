@@ -0,0 +1,301 @@
+use anyhow::{anyhow, Context, Error};
+use serde::Serialize;
+use std::{convert::TryFrom, io, str::FromStr};
+
+use super::{rules::Schema, Passport, ValidPassport};
+use crate::input::{lines, records, ParseMode};
+
+/// The formats passports can be read from and written to
+///
+/// Batch is the puzzle's own `key:value` format, JSON Lines has one JSON
+/// object per passport per line, and CSV has a header row followed by one
+/// row per passport. JSON and CSV use the long field names, e.g.
+/// `birth_year` rather than `byr`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Batch,
+    JsonLines,
+    Csv,
+}
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "batch" => Ok(Format::Batch),
+            "jsonl" => Ok(Format::JsonLines),
+            "csv" => Ok(Format::Csv),
+            _ => Err(anyhow!(
+                "Unknown format {}, expected batch, jsonl or csv",
+                s
+            )),
+        }
+    }
+}
+
+/// Read passports in the given format
+///
+/// Each passport is parsed on its own, so one bad record doesn't stop the
+/// rest from being read.
+pub fn read_passports(input: &str, format: Format) -> Vec<Result<Passport, Error>> {
+    match format {
+        Format::Batch => records(input)
+            .map(|record| {
+                Passport::parse(record.text, ParseMode::Lenient)
+                    .map(|passport| passport.offset_lines(record.lines.start - 1))
+                    .map_err(|e| record.relocate(e).into())
+            })
+            .collect(),
+        Format::JsonLines => lines(input)
+            .filter(|line| !line.text.trim().is_empty())
+            .map(|line| {
+                serde_json::from_str(line.text)
+                    .map_err(Error::from)
+                    .and_then(with_required_fields)
+                    .with_context(|| {
+                        format!("Invalid passport at line {}", line.number)
+                    })
+            })
+            .collect(),
+        Format::Csv => csv::Reader::from_reader(input.as_bytes())
+            .into_deserialize()
+            .map(|row| row.map_err(Error::from).and_then(with_required_fields))
+            .collect(),
+    }
+}
+
+/// Check that a passport that was read without `Passport::parse` has all the
+/// fields the built-in rules require, like one that was parsed would
+fn with_required_fields(passport: Passport) -> Result<Passport, Error> {
+    match passport.missing_field(Schema::passport()) {
+        Some(key) => Err(anyhow!("Missing field {}", key)),
+        None => Ok(passport),
+    }
+}
+
+/// Read passports in the given format and validate them
+pub fn read_valid_passports(
+    input: &str,
+    format: Format,
+) -> Vec<Result<ValidPassport, Error>> {
+    read_passports(input, format)
+        .into_iter()
+        .map(|passport| Ok(ValidPassport::try_from(passport?)?))
+        .collect()
+}
+
+/// Writes passports in the batch format
+///
+/// Fields are written in the given order, with any fields that aren't
+/// mentioned following in the usual `byr iyr eyr hgt hcl ecl pid cid` order.
+/// If a maximum width is set, fields are wrapped onto new lines so that no
+/// line is longer than it, unless a single field is longer by itself.
+#[derive(Debug, Clone, Default)]
+pub struct BatchWriter {
+    order: Vec<String>,
+    max_width: Option<usize>,
+}
+impl BatchWriter {
+    /// Set the order of the fields, by key
+    pub fn order<I, S>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.order = keys.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Wrap lines longer than `width`
+    pub fn max_width(mut self, width: usize) -> Self {
+        self.max_width = Some(width);
+        self
+    }
+
+    /// Write a passport as a batch record, with no trailing newline
+    ///
+    /// This fails if any value is empty or contains whitespace, since the
+    /// record couldn't be parsed back into the same passport.
+    pub fn write(&self, passport: &Passport) -> Result<String, Error> {
+        passport
+            .fields()
+            .iter()
+            .find(|(_, v)| v.is_empty() || v.contains(char::is_whitespace))
+            .map_or(Ok(()), |(k, v)| {
+                Err(anyhow!("Cannot write field {} with value {:?}", k, v))
+            })?;
+        let fields = passport.fields();
+        let keys = self
+            .order
+            .iter()
+            .map(String::as_str)
+            .chain(Passport::KNOWN_KEYS.iter().copied())
+            .filter(|key| fields.contains_key(key));
+        let mut out = String::new();
+        let mut line_len = 0;
+        let mut written = Vec::with_capacity(fields.len());
+        for key in keys {
+            if written.contains(&key) {
+                continue;
+            }
+            written.push(key);
+            let token = format!("{}:{}", key, fields[key]);
+            if line_len > 0 {
+                match self.max_width {
+                    Some(width) if line_len + 1 + token.len() > width => {
+                        out.push('\n');
+                        line_len = 0;
+                    }
+                    _ => {
+                        out.push(' ');
+                        line_len += 1;
+                    }
+                }
+            }
+            line_len += token.len();
+            out.push_str(&token);
+        }
+        Ok(out)
+    }
+}
+
+/// Write passports, valid or otherwise, in the given format
+pub fn write_passports<T, W>(
+    passports: &[T],
+    format: Format,
+    writer: W,
+) -> Result<(), Error>
+where
+    T: Serialize + Clone + Into<Passport>,
+    W: io::Write,
+{
+    match format {
+        Format::Batch => {
+            let mut writer = writer;
+            let batch = BatchWriter::default();
+            passports
+                .iter()
+                .enumerate()
+                .try_for_each(|(idx, passport)| {
+                    if idx > 0 {
+                        writeln!(writer)?;
+                    }
+                    writeln!(writer, "{}", batch.write(&passport.clone().into())?)?;
+                    Ok(())
+                })
+        }
+        Format::JsonLines => {
+            let mut writer = writer;
+            passports.iter().try_for_each(|passport| {
+                serde_json::to_writer(&mut writer, passport)?;
+                writeln!(writer)?;
+                Ok(())
+            })
+        }
+        Format::Csv => {
+            let mut writer = csv::Writer::from_writer(writer);
+            passports
+                .iter()
+                .try_for_each(|passport| writer.serialize(passport))?;
+            writer.flush()?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    static BATCH: &str = "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd
+byr:1937 iyr:2017 cid:147 hgt:183cm
+
+iyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884
+hcl:#cfa07d byr:1929
+
+hcl:#ae17e1 iyr:2013 eyr:2024 ecl:brn pid:760753108 byr:1931 hgt:179cm
+";
+
+    fn round_trip(format: Format) {
+        let passports = read_valid_passports(BATCH, Format::Batch)
+            .into_iter()
+            .filter_map(Result::ok)
+            .collect::<Vec<_>>();
+        assert_eq!(passports.len(), 2);
+        let mut out = Vec::new();
+        write_passports(&passports, format, &mut out).unwrap();
+        let read = read_valid_passports(&String::from_utf8(out).unwrap(), format)
+            .into_iter()
+            .collect::<Result<Vec<_>, Error>>()
+            .unwrap();
+        assert_eq!(read, passports);
+    }
+
+    #[test]
+    fn batch_round_trip() {
+        round_trip(Format::Batch);
+    }
+
+    #[test]
+    fn batch_writer_orders_and_wraps() {
+        let passport = Passport::try_from(records(BATCH).next().unwrap().text).unwrap();
+        assert_eq!(
+            BatchWriter::default().write(&passport).unwrap(),
+            "byr:1937 iyr:2017 eyr:2020 hgt:183cm hcl:#fffffd ecl:gry pid:860033327 cid:147"
+        );
+        let written = BatchWriter::default()
+            .order(vec!["pid", "cid", "nope"])
+            .max_width(30)
+            .write(&passport)
+            .unwrap();
+        assert_eq!(
+            written,
+            "pid:860033327 cid:147 byr:1937\niyr:2017 eyr:2020 hgt:183cm\nhcl:#fffffd ecl:gry"
+        );
+        assert_eq!(Passport::try_from(written.as_str()).unwrap(), passport);
+    }
+
+    #[test]
+    fn batch_writer_rejects_unparseable_values() {
+        let mut passport =
+            Passport::try_from(records(BATCH).next().unwrap().text).unwrap();
+        passport.height = Some("183 cm".into());
+        assert!(BatchWriter::default().write(&passport).is_err());
+    }
+
+    #[test]
+    fn json_lines_round_trip() {
+        round_trip(Format::JsonLines);
+    }
+
+    #[test]
+    fn csv_round_trip() {
+        round_trip(Format::Csv);
+    }
+
+    #[test]
+    fn bad_records_do_not_stop_reading() {
+        let input = r##"{"birth_year": "1937"}
+{"birth_year":"1937","issue_year":"2017","expiration_year":"2020","height":"183cm","hair_color":"#fffffd","eye_color":"gry","passport_id":"860033327"}
+"##;
+        let read = read_passports(input, Format::JsonLines);
+        assert!(read[0].is_err());
+        assert_eq!(read[1].as_ref().unwrap().country_id, None);
+
+        let read = read_passports(&format!("byr:1937\n\n{}", BATCH), Format::Batch);
+        assert_eq!(
+            read[0].as_ref().err().unwrap().to_string(),
+            "Missing field iyr at line 1, column 9"
+        );
+        assert!(read[1].is_ok());
+
+        let bad_id =
+            "byr:1937 iyr:2017 eyr:2020 hgt:183cm hcl:#fffffd ecl:gry pid:8600";
+        let read =
+            read_valid_passports(&format!("{}\n{}", BATCH, bad_id), Format::Batch);
+        assert_eq!(
+            read[3].as_ref().err().unwrap().to_string(),
+            "Invalid passport ID \"8600\" at line 9, column 62"
+        );
+    }
+}
@@ -0,0 +1,326 @@
+use anyhow::{anyhow, Error};
+use std::{
+    cmp::Ordering,
+    convert::TryFrom,
+    fmt,
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
+
+// Heights are stored as an integer number of micrometres, which lets us
+// represent every unit we support (and any sensible number of decimal
+// places) exactly, so that e.g. 2.54cm and 1in compare equal.
+const MICROMETRES_PER_MM: u64 = 1_000;
+const MICROMETRES_PER_CM: u64 = 10_000;
+const MICROMETRES_PER_IN: u64 = 25_400;
+const MICROMETRES_PER_FT: u64 = 12 * MICROMETRES_PER_IN;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HeightUnit {
+    Millimeters,
+    Centimeters,
+    Inches,
+    /// Feet and inches, written like `5'10"`
+    FeetAndInches,
+}
+impl HeightUnit {
+    /// The unit that validation bounds are expressed in
+    pub const CANONICAL: HeightUnit = HeightUnit::Centimeters;
+
+    /// How many micrometres there are in one of this unit. Feet and inches
+    /// are measured in feet.
+    fn micrometres(self) -> u64 {
+        match self {
+            HeightUnit::Millimeters => MICROMETRES_PER_MM,
+            HeightUnit::Centimeters => MICROMETRES_PER_CM,
+            HeightUnit::Inches => MICROMETRES_PER_IN,
+            HeightUnit::FeetAndInches => MICROMETRES_PER_FT,
+        }
+    }
+
+    /// The name of the unit, as used in config files
+    pub fn name(self) -> &'static str {
+        match self {
+            HeightUnit::Millimeters => "mm",
+            HeightUnit::Centimeters => "cm",
+            HeightUnit::Inches => "in",
+            HeightUnit::FeetAndInches => "ft",
+        }
+    }
+}
+impl FromStr for HeightUnit {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "mm" => Ok(HeightUnit::Millimeters),
+            "cm" => Ok(HeightUnit::Centimeters),
+            "in" => Ok(HeightUnit::Inches),
+            "ft" => Ok(HeightUnit::FeetAndInches),
+            _ => Err(anyhow!("Unknown height unit {}", s)),
+        }
+    }
+}
+
+/// Parse a non-negative decimal number like `170` or `170.5` into an integer
+/// count of some fraction of the number, e.g. micrometres when `scale` is
+/// the number of micrometres in the number's unit. Rounds to the nearest.
+fn parse_scaled(number: &str, scale: u64) -> Option<u64> {
+    let (whole, fraction) = match number.find('.') {
+        Some(idx) => (&number[..idx], &number[idx + 1..]),
+        None => (number, ""),
+    };
+    let is_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    // we need something before the point, and nothing silly after it
+    if whole.is_empty() || !is_digits(whole) || !is_digits(fraction) {
+        return None;
+    }
+    if fraction.len() > 9 || number.ends_with('.') {
+        return None;
+    }
+    let whole = whole.parse::<u64>().ok()?.checked_mul(scale)?;
+    let fraction = match fraction {
+        "" => 0,
+        _ => {
+            let denominator = 10u128.pow(fraction.len() as u32);
+            let numerator = fraction.parse::<u128>().ok()? * u128::from(scale);
+            ((numerator + denominator / 2) / denominator) as u64
+        }
+    };
+    whole.checked_add(fraction)
+}
+
+/// Format `amount` of something that there are `scale` of per unit, with as
+/// few decimal places as `parse_scaled` needs to read back the same amount
+///
+/// Not every amount has an exact decimal, e.g. 1µm is 1/25400 of an inch,
+/// but nine decimal places are always enough to round to the right one.
+fn format_scaled(amount: u64, scale: u64) -> String {
+    let whole = amount / scale;
+    let remainder = u128::from(amount % scale);
+    if remainder == 0 {
+        return whole.to_string();
+    }
+    let scale = u128::from(scale);
+    let (places, fraction) = (1..=9)
+        .map(|places| {
+            let denominator = 10u128.pow(places);
+            (places, (remainder * denominator + scale / 2) / scale)
+        })
+        .find(|(places, fraction)| {
+            let denominator = 10u128.pow(*places);
+            (fraction * scale + denominator / 2) / denominator == remainder
+        })
+        .expect("nine decimal places are enough for any unit");
+    let digits = format!("{:0width$}", fraction, width = places as usize);
+    format!("{}.{}", whole, digits.trim_end_matches('0'))
+}
+
+/// A height, in one of several units
+///
+/// Heights in different units can be compared with one another, and
+/// equality ignores the unit, so `Height::from_str("1in") ==
+/// Height::from_str("2.54cm")`.
+#[derive(Debug, Clone, Copy)]
+pub struct Height {
+    micrometres: u64,
+    unit: HeightUnit,
+}
+impl Height {
+    /// A height of `value` in the given unit. Feet and inches heights are
+    /// given in feet.
+    pub fn new(value: f64, unit: HeightUnit) -> Self {
+        Self {
+            micrometres: (value.max(0.0) * unit.micrometres() as f64).round() as u64,
+            unit,
+        }
+    }
+
+    pub fn unit(self) -> HeightUnit {
+        self.unit
+    }
+
+    /// The numeric value of the height in its own unit
+    pub fn value(self) -> f64 {
+        self.value_in(self.unit)
+    }
+
+    /// The numeric value of the height in the given unit
+    pub fn value_in(self, unit: HeightUnit) -> f64 {
+        self.micrometres as f64 / unit.micrometres() as f64
+    }
+
+    /// The same height, expressed in a different unit
+    pub fn to_unit(self, unit: HeightUnit) -> Self {
+        Self {
+            micrometres: self.micrometres,
+            unit,
+        }
+    }
+
+    /// The same height, expressed in the canonical unit
+    pub fn canonical(self) -> Self {
+        self.to_unit(HeightUnit::CANONICAL)
+    }
+}
+impl PartialEq for Height {
+    fn eq(&self, other: &Self) -> bool {
+        self.micrometres == other.micrometres
+    }
+}
+impl Eq for Height {}
+impl PartialOrd for Height {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for Height {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.micrometres.cmp(&other.micrometres)
+    }
+}
+impl Hash for Height {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.micrometres.hash(state);
+    }
+}
+impl TryFrom<&str> for Height {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let value = value.trim();
+        let invalid =
+            || anyhow!("Value {} does not contain a number and a unit", value);
+
+        // Feet and inches are the odd one out, e.g. 5'10" or just 6'
+        if let Some(idx) = value.find('\'') {
+            let feet = parse_scaled(&value[..idx], MICROMETRES_PER_FT);
+            let inches = match &value[idx + 1..] {
+                "" => Some(0),
+                rest => rest
+                    .strip_suffix('"')
+                    .and_then(|inches| parse_scaled(inches, MICROMETRES_PER_IN)),
+            };
+            return feet
+                .zip(inches)
+                .and_then(|(feet, inches)| feet.checked_add(inches))
+                .map(|micrometres| Self {
+                    micrometres,
+                    unit: HeightUnit::FeetAndInches,
+                })
+                .ok_or_else(invalid);
+        }
+
+        // Parse the unit. Input specifies case-sensitvity
+        let unit = [
+            HeightUnit::Millimeters,
+            HeightUnit::Centimeters,
+            HeightUnit::Inches,
+        ]
+        .iter()
+        .find(|unit| value.ends_with(unit.name()))
+        .copied()
+        .ok_or_else(|| anyhow!("Value {} does not have a unit", value))?;
+
+        let number = &value[..value.len() - unit.name().len()];
+        parse_scaled(number, unit.micrometres())
+            .map(|micrometres| Self { micrometres, unit })
+            .ok_or_else(invalid)
+    }
+}
+impl FromStr for Height {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(s)
+    }
+}
+impl fmt::Display for Height {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.unit {
+            HeightUnit::FeetAndInches => {
+                let feet = self.micrometres / MICROMETRES_PER_FT;
+                let inches = self.micrometres % MICROMETRES_PER_FT;
+                write!(
+                    f,
+                    "{}'{}\"",
+                    feet,
+                    format_scaled(inches, MICROMETRES_PER_IN)
+                )
+            }
+            unit => write!(
+                f,
+                "{}{}",
+                format_scaled(self.micrometres, unit.micrometres()),
+                unit.name()
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn height(s: &str) -> Height {
+        Height::try_from(s).unwrap()
+    }
+
+    #[test]
+    fn heights_round_trip_through_strings() {
+        [
+            "170cm", "170.5cm", "1705mm", "59in", "70.25in", "5'10\"", "6'0.5\"",
+        ]
+        .iter()
+        .for_each(|s| assert_eq!(height(s).to_string(), *s));
+        assert_eq!(height("6'").to_string(), "6'0\"");
+        // 25401µm, which has no exact decimal in inches
+        assert_eq!(height("1.00003in").to_string(), "1.00004in");
+        assert_eq!(height("170.0001cm").to_string(), "170.0001cm");
+        [
+            HeightUnit::Millimeters,
+            HeightUnit::Centimeters,
+            HeightUnit::Inches,
+            HeightUnit::FeetAndInches,
+        ]
+        .iter()
+        .for_each(|unit| {
+            (1_500_000..1_560_000).step_by(7).for_each(|micrometres| {
+                let exact = Height {
+                    micrometres,
+                    unit: *unit,
+                };
+                assert_eq!(height(&exact.to_string()), exact, "{:?}", exact);
+            })
+        });
+    }
+
+    #[test]
+    fn heights_compare_across_units() {
+        assert_eq!(height("1in"), height("2.54cm"));
+        assert_eq!(height("5'10\""), height("70in"));
+        assert_eq!(height("1705mm"), height("170.5cm"));
+        assert!(height("59in") < height("150cm"));
+        assert!(height("76in") > height("193cm"));
+        assert_eq!(
+            height("70in").to_unit(HeightUnit::Centimeters).to_string(),
+            "177.8cm"
+        );
+        assert_eq!(
+            height("177.8cm")
+                .to_unit(HeightUnit::FeetAndInches)
+                .to_string(),
+            "5'10\""
+        );
+        assert!((height("5'").value_in(HeightUnit::Inches) - 60.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn bad_heights_are_rejected() {
+        [
+            "190", "cm", "1.cm", ".5cm", "1.2.3in", "-5cm", "5'10", "a'1\"",
+        ]
+        .iter()
+        .for_each(|s| assert!(Height::try_from(*s).is_err(), "{}", s));
+    }
+}
@@ -0,0 +1,264 @@
+use anyhow::{anyhow, Error};
+use std::{cmp::Reverse, fmt};
+
+use super::rules::{FieldRule, Schema};
+use super::Passport;
+
+/// How sure we are that a suggested fix is what was meant
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Confidence {
+    Low,
+    Medium,
+    High,
+}
+
+/// A change to one field that would make it valid
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    pub key: String,
+    pub original: String,
+    pub replacement: String,
+    pub confidence: Confidence,
+    pub reason: &'static str,
+}
+impl fmt::Display for Suggestion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} -> {} ({}, {:?} confidence)",
+            self.key, self.original, self.replacement, self.reason, self.confidence
+        )
+    }
+}
+
+/// The number of single-character insertions, deletions and substitutions
+/// needed to turn one string into the other
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<char>>();
+    let mut prev = (0..=b.len()).collect::<Vec<usize>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut row = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + if ca == *cb { 0 } else { 1 };
+            row[j + 1] = substitution.min(prev[j + 1] + 1).min(row[j] + 1);
+        }
+        prev = row;
+    }
+    prev[b.len()]
+}
+
+/// Everything we might try to turn an invalid value into, most likely first.
+/// None of these are checked against the field's rule yet.
+fn candidates(
+    value: &str,
+    rule: &FieldRule,
+) -> Vec<(String, Confidence, &'static str)> {
+    let mut out = Vec::new();
+    let clean = value.trim().to_lowercase();
+    if clean != value {
+        out.push((clean.clone(), Confidence::High, "fix case and whitespace"));
+    }
+    if !clean.starts_with('#') {
+        out.push((format!("#{}", clean), Confidence::High, "add missing #"));
+    }
+
+    let is_number = !clean.is_empty() && clean.chars().all(|c| c.is_ascii_digit());
+    if is_number {
+        ["cm", "in", "mm"].iter().for_each(|unit| {
+            out.push((
+                format!("{}{}", clean, unit),
+                Confidence::Medium,
+                "add missing unit",
+            ))
+        });
+        // IDs missing leading zeros, or with too many of them
+        (1..=3).for_each(|zeros| {
+            out.push((
+                format!("{}{}", "0".repeat(zeros), clean),
+                Confidence::Medium,
+                "add leading zeros",
+            ))
+        });
+        let trimmed = clean.trim_start_matches('0');
+        if trimmed != clean && !trimmed.is_empty() {
+            out.push((
+                trimmed.to_owned(),
+                Confidence::Medium,
+                "remove leading zeros",
+            ));
+        }
+        if clean.len() == 2 {
+            ["19", "20"].iter().for_each(|century| {
+                out.push((
+                    format!("{}{}", century, clean),
+                    Confidence::Medium,
+                    "expand year",
+                ))
+            });
+        }
+    }
+
+    ["cm", "in"].iter().for_each(|unit| {
+        if let Some(number) = clean.strip_suffix(unit) {
+            let other = if *unit == "cm" { "in" } else { "cm" };
+            out.push((format!("{}{}", number, other), Confidence::Low, "swap unit"));
+        }
+    });
+
+    if let Some(values) = rule.allowed_values() {
+        values
+            .iter()
+            .for_each(|allowed| match edit_distance(&clean, allowed) {
+                1 => out.push((allowed.clone(), Confidence::Medium, "fix typo")),
+                2 => out.push((allowed.clone(), Confidence::Low, "fix typo")),
+                _ => {}
+            });
+    }
+    out
+}
+
+/// Suggest fixes for every invalid field in a passport
+///
+/// Each suggestion on its own makes its field valid. Only the most confident
+/// fixes for a field are suggested, smallest change first. If several
+/// equally good fixes are possible for a field, they're all downgraded to
+/// low confidence, since we can't tell which was meant. Missing fields and
+/// fields we can't make sense of get no suggestions.
+pub fn suggest_fixes(passport: &Passport, schema: &Schema) -> Vec<Suggestion> {
+    let fields = passport.fields();
+    let mut keys = fields.keys().copied().collect::<Vec<&str>>();
+    keys.sort_by_key(|key| Passport::KNOWN_KEYS.iter().position(|k| k == key));
+    keys.into_iter()
+        .filter_map(|key| {
+            let rule = schema.field(key)?;
+            let value = fields[key];
+            if rule.check(value).is_ok() {
+                return None;
+            }
+            let mut suggestions: Vec<Suggestion> = Vec::new();
+            candidates(value, rule)
+                .into_iter()
+                .filter(|(candidate, _, _)| rule.check(candidate).is_ok())
+                .for_each(|(replacement, confidence, reason)| {
+                    // the first way we found to get to a value is the best one
+                    if suggestions.iter().all(|s| s.replacement != replacement) {
+                        suggestions.push(Suggestion {
+                            key: key.to_owned(),
+                            original: value.to_owned(),
+                            replacement,
+                            confidence,
+                            reason,
+                        });
+                    }
+                });
+            suggestions.sort_by_key(|s| {
+                (
+                    Reverse(s.confidence),
+                    edit_distance(&s.original, &s.replacement),
+                )
+            });
+            if let Some(best) = suggestions.first().map(|s| s.confidence) {
+                suggestions.retain(|s| s.confidence == best);
+            }
+            let ambiguous = suggestions.len() > 1
+                && suggestions[0].confidence == suggestions[1].confidence
+                && edit_distance(&suggestions[0].original, &suggestions[0].replacement)
+                    == edit_distance(
+                        &suggestions[1].original,
+                        &suggestions[1].replacement,
+                    );
+            if ambiguous {
+                suggestions
+                    .iter_mut()
+                    .for_each(|s| s.confidence = Confidence::Low);
+            }
+            Some(suggestions)
+        })
+        .flatten()
+        .collect()
+}
+
+/// Apply the best fix for each invalid field, as long as it has at least
+/// the given confidence
+///
+/// Returns the repaired passport and the fixes that were applied, or an
+/// error naming a field that couldn't be fixed confidently enough.
+pub fn repair(
+    passport: &Passport,
+    schema: &Schema,
+    min_confidence: Confidence,
+) -> Result<(Passport, Vec<Suggestion>), Error> {
+    let mut repaired = passport.clone();
+    let mut applied: Vec<Suggestion> = Vec::new();
+    for suggestion in suggest_fixes(passport, schema) {
+        if applied.iter().any(|s| s.key == suggestion.key) {
+            continue;
+        }
+        if suggestion.confidence < min_confidence {
+            return Err(anyhow!("No confident fix for {}", suggestion));
+        }
+        repaired.set_field(&suggestion.key, suggestion.replacement.clone())?;
+        applied.push(suggestion);
+    }
+    // anything still invalid had no suggestions at all
+    schema.validate(&repaired.fields(), |key| repaired.location(key))?;
+    Ok((repaired, applied))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::convert::TryFrom;
+
+    fn fixes(input: &str) -> Vec<(String, Confidence)> {
+        suggest_fixes(&Passport::try_from(input).unwrap(), Schema::passport())
+            .into_iter()
+            .map(|s| (s.replacement, s.confidence))
+            .collect()
+    }
+
+    #[test]
+    fn suggests_fixes_for_common_mistakes() {
+        let input =
+            "byr:1937 iyr:2017 eyr:2020 hgt:190 hcl:123abc ecl:BRN pid:12345678";
+        assert_eq!(
+            fixes(input),
+            vec![
+                ("190cm".to_owned(), Confidence::Medium),
+                ("#123abc".to_owned(), Confidence::High),
+                ("brn".to_owned(), Confidence::High),
+                ("012345678".to_owned(), Confidence::Medium),
+            ]
+        );
+    }
+
+    #[test]
+    fn ambiguous_fixes_are_low_confidence() {
+        // "grx" is one typo away from both "gry" and "grn"
+        let input =
+            "byr:1937 iyr:2017 eyr:2020 hgt:190cm hcl:#123abc ecl:grx pid:012345678";
+        assert!(fixes(input).iter().all(|(_, c)| *c == Confidence::Low));
+        assert!(repair(
+            &Passport::try_from(input).unwrap(),
+            Schema::passport(),
+            Confidence::Medium
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn repaired_passports_are_valid() {
+        let input =
+            "byr:1937 iyr:2017 eyr:2020 hgt:70 hcl:#123ABC ecl:amb pid:0123456789";
+        let (repaired, applied) = repair(
+            &Passport::try_from(input).unwrap(),
+            Schema::passport(),
+            Confidence::Medium,
+        )
+        .unwrap();
+        assert_eq!(applied.len(), 3);
+        assert_eq!(repaired.height.as_deref(), Some("70in"));
+        assert_eq!(repaired.passport_id.as_deref(), Some("123456789"));
+        assert!(super::super::ValidPassport::try_from(repaired).is_ok());
+    }
+}
@@ -0,0 +1,413 @@
+use anyhow::{anyhow, Error};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::TryFrom,
+    fs,
+    path::Path,
+};
+
+use super::height::{Height, HeightUnit};
+use crate::error::{Location, ParseError};
+
+static PASSPORT_RULES: &str = include_str!("../day4.rules.toml");
+
+lazy_static! {
+    static ref PASSPORT_SCHEMA: Schema =
+        Schema::from_toml(PASSPORT_RULES).expect("built-in passport rules are valid");
+}
+
+/// What kind of value a field holds, and the constraints on it, as written
+/// in the config file
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum RawFieldKind {
+    Integer {
+        range: (i64, i64),
+    },
+    /// `measurement` is what height rules were called before heights could
+    /// be converted between units
+    #[serde(alias = "measurement")]
+    Height {
+        range: Option<(f64, f64)>,
+        units: Option<RawUnits>,
+    },
+    Pattern {
+        pattern: String,
+    },
+    Enum {
+        values: Vec<String>,
+    },
+    Any,
+}
+
+/// The units a height may be written in, optionally with bounds for each
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum RawUnits {
+    Names(Vec<String>),
+    Bounds(BTreeMap<String, (f64, f64)>),
+}
+
+fn required_default() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize)]
+struct RawFieldRule {
+    name: Option<String>,
+    #[serde(default = "required_default")]
+    required: bool,
+    #[serde(flatten)]
+    kind: RawFieldKind,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawSchema {
+    fields: BTreeMap<String, RawFieldRule>,
+}
+
+/// The lowest and highest heights allowed, inclusive
+type HeightRange = (Height, Height);
+
+/// The constraints on a field's value, ready to be checked
+#[derive(Debug)]
+enum FieldKind {
+    Integer {
+        min: i64,
+        max: i64,
+    },
+    Height {
+        range: Option<HeightRange>,
+        /// The units that may be used, with the bounds for values in each
+        units: Option<Vec<(HeightUnit, Option<HeightRange>)>>,
+    },
+    Pattern(Regex),
+    Enum(Vec<String>),
+    Any,
+}
+
+/// The rule for a single field in a document
+#[derive(Debug)]
+pub struct FieldRule {
+    name: String,
+    required: bool,
+    kind: FieldKind,
+}
+impl FieldRule {
+    fn from_raw(key: &str, raw: RawFieldRule) -> Result<Self, Error> {
+        let kind = match raw.kind {
+            RawFieldKind::Integer { range: (min, max) } => {
+                if min > max {
+                    return Err(anyhow!("Field {} has an empty range", key));
+                }
+                FieldKind::Integer { min, max }
+            }
+            RawFieldKind::Height { range, units } => {
+                // bounds are converted once here, so checks compare exactly
+                let bounds = |unit: HeightUnit, (min, max): (f64, f64)| {
+                    if min > max {
+                        return Err(anyhow!("Field {} has an empty range", key));
+                    }
+                    Ok((Height::new(min, unit), Height::new(max, unit)))
+                };
+                let units = match units {
+                    None => None,
+                    Some(RawUnits::Names(names)) => Some(
+                        names
+                            .iter()
+                            .map(|name| Ok((name.parse()?, None)))
+                            .collect::<Result<_, Error>>()?,
+                    ),
+                    Some(RawUnits::Bounds(units)) => Some(
+                        units
+                            .iter()
+                            .map(|(name, range)| {
+                                let unit = name.parse()?;
+                                Ok((unit, Some(bounds(unit, *range)?)))
+                            })
+                            .collect::<Result<_, Error>>()?,
+                    ),
+                };
+                FieldKind::Height {
+                    range: range
+                        .map(|range| bounds(HeightUnit::CANONICAL, range))
+                        .transpose()?,
+                    units,
+                }
+            }
+            RawFieldKind::Pattern { pattern } => {
+                FieldKind::Pattern(Regex::new(&pattern).map_err(|e| {
+                    anyhow!("Field {} has an invalid pattern: {}", key, e)
+                })?)
+            }
+            RawFieldKind::Enum { values } => FieldKind::Enum(values),
+            RawFieldKind::Any => FieldKind::Any,
+        };
+        Ok(Self {
+            name: raw.name.unwrap_or_else(|| key.to_owned()),
+            required: raw.required,
+            kind,
+        })
+    }
+
+    /// The human-readable name of the field
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether a document is invalid without this field
+    pub fn required(&self) -> bool {
+        self.required
+    }
+
+    /// The values the field may take, if it is limited to a set of them
+    pub fn allowed_values(&self) -> Option<&[String]> {
+        match &self.kind {
+            FieldKind::Enum(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Check a value against the rule, locating any error within `value`
+    pub fn check(&self, value: &str) -> Result<(), ParseError> {
+        let invalid = || ParseError::Invalid {
+            at: Location::of(value, value.trim()),
+            what: self.name.clone(),
+        };
+        let value = value.trim();
+        match &self.kind {
+            FieldKind::Integer { min, max } => value
+                .parse::<i64>()
+                .ok()
+                .filter(|i| (min..=max).contains(&i))
+                .map(|_| ())
+                .ok_or_else(invalid),
+            FieldKind::Height { range, units } => {
+                let height = Height::try_from(value).map_err(|_| invalid())?;
+                let within = |bounds: &Option<HeightRange>| {
+                    bounds.is_none_or(|(min, max)| (min..=max).contains(&height))
+                };
+                let unit_allowed = units.as_ref().is_none_or(|units| {
+                    units
+                        .iter()
+                        .any(|(unit, bounds)| *unit == height.unit() && within(bounds))
+                });
+                match unit_allowed && within(range) {
+                    true => Ok(()),
+                    false => Err(invalid()),
+                }
+            }
+            FieldKind::Pattern(re) => match re.is_match(value) {
+                true => Ok(()),
+                false => Err(invalid()),
+            },
+            FieldKind::Enum(values) => match values.iter().any(|v| v == value) {
+                true => Ok(()),
+                false => Err(invalid()),
+            },
+            FieldKind::Any => Ok(()),
+        }
+    }
+}
+
+/// A set of field rules describing a valid document
+///
+/// Schemas are loaded from TOML or JSON. See `day4.rules.toml` for the
+/// rules used for passports, which also documents the format.
+#[derive(Debug)]
+pub struct Schema {
+    fields: BTreeMap<String, FieldRule>,
+}
+impl Schema {
+    fn from_raw(raw: RawSchema) -> Result<Self, Error> {
+        Ok(Self {
+            fields: raw
+                .fields
+                .into_iter()
+                .map(|(key, rule)| {
+                    FieldRule::from_raw(&key, rule).map(|rule| (key, rule))
+                })
+                .collect::<Result<_, Error>>()?,
+        })
+    }
+
+    /// The built-in rules for passports
+    pub fn passport() -> &'static Self {
+        &PASSPORT_SCHEMA
+    }
+
+    pub fn from_toml(config: &str) -> Result<Self, Error> {
+        Self::from_raw(toml::from_str(config)?)
+    }
+
+    pub fn from_json(config: &str) -> Result<Self, Error> {
+        Self::from_raw(serde_json::from_str(config)?)
+    }
+
+    /// Load a schema from a file, using its extension to pick the format
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let config = fs::read_to_string(path)?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Self::from_toml(&config),
+            Some("json") => Self::from_json(&config),
+            _ => Err(anyhow!(
+                "Cannot tell the format of {}, expected .toml or .json",
+                path.display()
+            )),
+        }
+    }
+
+    /// The rule for the field with the given key
+    pub fn field(&self, key: &str) -> Option<&FieldRule> {
+        self.fields.get(key)
+    }
+
+    /// Iterate over (key, rule) for every field in the schema
+    pub fn fields(&self) -> impl Iterator<Item = (&str, &FieldRule)> {
+        self.fields.iter().map(|(k, v)| (k.as_str(), v))
+    }
+
+    /// Check a single value against the rule for its key. Keys that are not
+    /// part of the schema are accepted as-is.
+    pub fn check(&self, key: &str, value: &str) -> Result<(), ParseError> {
+        self.field(key).map_or(Ok(()), |rule| rule.check(value))
+    }
+
+    /// Check a full record of key/value pairs, ensuring that all required
+    /// fields are present and that every field is valid
+    ///
+    /// `locate` gives where a key's value is in the input, or where it
+    /// should have been if it's missing, for reporting errors.
+    pub fn validate<F>(
+        &self,
+        record: &HashMap<&str, &str>,
+        locate: F,
+    ) -> Result<(), ParseError>
+    where
+        F: Fn(&str) -> Location,
+    {
+        self.fields
+            .iter()
+            .try_for_each(|(key, rule)| match record.get(key.as_str()) {
+                Some(value) => rule.check(value).map_err(|_| ParseError::Invalid {
+                    at: locate(key),
+                    what: rule.name.clone(),
+                }),
+                None if rule.required => Err(ParseError::Missing {
+                    at: locate(key),
+                    what: rule.name.clone(),
+                }),
+                None => Ok(()),
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn passport_rules_match_puzzle_examples() {
+        let schema = Schema::passport();
+        let cases = [
+            ("byr", "2002", true),
+            ("byr", "2003", false),
+            ("hgt", "60in", true),
+            ("hgt", "190cm", true),
+            ("hgt", "190in", false),
+            ("hgt", "190", false),
+            ("hgt", "150cm", true),
+            ("hgt", "149.9cm", false),
+            ("hgt", "193cm", true),
+            ("hgt", "193.04cm", false),
+            ("hgt", "59in", true),
+            ("hgt", "58.9in", false),
+            ("hgt", "1700mm", false),
+            ("hcl", "#123abc", true),
+            ("hcl", "#123abz", false),
+            ("hcl", "123abc", false),
+            ("ecl", "brn", true),
+            ("ecl", "wat", false),
+            ("pid", "000000001", true),
+            ("pid", "0123456789", false),
+        ];
+        cases.iter().for_each(|(key, value, valid)| {
+            assert_eq!(
+                schema.check(key, value).is_ok(),
+                *valid,
+                "{}:{}",
+                key,
+                value
+            );
+        });
+    }
+
+    #[test]
+    fn measurement_rules_still_load() {
+        let schema = Schema::from_toml(
+            r#"
+            [fields.hgt]
+            type = "measurement"
+            units = { cm = [150, 193], in = [59, 76] }
+            "#,
+        )
+        .unwrap();
+        let cases = [
+            ("150cm", true),
+            ("149cm", false),
+            ("76in", true),
+            ("193.04cm", false),
+            ("5'10\"", false),
+        ];
+        cases.iter().for_each(|(value, valid)| {
+            assert_eq!(schema.check("hgt", value).is_ok(), *valid, "{}", value);
+        });
+        assert!(
+            Schema::from_toml("[fields.h]\ntype = \"height\"\nrange = [2, 1]").is_err()
+        );
+    }
+
+    #[test]
+    fn json_and_toml_schemas_agree() {
+        let toml = Schema::from_toml(
+            r#"
+            [fields.lvl]
+            type = "integer"
+            range = [1, 10]
+
+            [fields.tag]
+            type = "pattern"
+            pattern = '^[A-Z]{3}$'
+            required = false
+            "#,
+        )
+        .unwrap();
+        let json = Schema::from_json(
+            r#"{"fields": {
+                "lvl": {"type": "integer", "range": [1, 10]},
+                "tag": {"type": "pattern", "pattern": "^[A-Z]{3}$", "required": false}
+            }}"#,
+        )
+        .unwrap();
+        let records: Vec<HashMap<&str, &str>> = vec![
+            [("lvl", "5")].iter().cloned().collect(),
+            [("lvl", "11")].iter().cloned().collect(),
+            [("lvl", "1"), ("tag", "ABC")].iter().cloned().collect(),
+            [("lvl", "1"), ("tag", "abc")].iter().cloned().collect(),
+            [("tag", "ABC")].iter().cloned().collect(),
+        ];
+        let expected = [true, false, true, false, false];
+        records
+            .iter()
+            .zip(expected.iter())
+            .for_each(|(record, valid)| {
+                let locate = |_: &str| Location::end_of("");
+                assert_eq!(toml.validate(record, locate).is_ok(), *valid);
+                assert_eq!(json.validate(record, locate).is_ok(), *valid);
+            });
+    }
+}
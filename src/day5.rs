@@ -1,23 +1,68 @@
 use anyhow::{anyhow, Error};
+use rand::{seq::SliceRandom, Rng};
 use std::cmp::max;
+use std::collections::HashSet;
 use std::convert::TryFrom;
+use std::fmt;
+use std::ops::{BitAnd, BitOr, Shl, Shr};
 
-static INPUT: &'static str = include_str!("day5.input");
+use crate::error::{Location, ParseError};
+use crate::input::parse_lines;
+use crate::runner::{Example, Solution};
 
-#[derive(Debug)]
-struct SearchSpecification {
+const INPUT: &str = include_str!("day5.input");
+const EXAMPLE: &str = include_str!("day5.example");
+
+/// An unsigned integer that a search specification can decode into
+pub trait SearchResult:
+    Copy
+    + PartialEq
+    + From<u8>
+    + Shl<u32, Output = Self>
+    + Shr<u32, Output = Self>
+    + BitOr<Output = Self>
+    + BitAnd<Output = Self>
+{
+    const BITS: u32;
+}
+macro_rules! impl_search_result {
+    ($($t:ty),*) => {
+        $(
+            impl SearchResult for $t {
+                const BITS: u32 = <$t>::BITS;
+            }
+        )*
+    };
+}
+impl_search_result!(u8, u16, u32, u64, u128, usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchSpecification {
     lower: char,
     upper: char,
-    length: u8,
+    length: u32,
 }
 impl SearchSpecification {
-    const fn new(lower: char, upper: char, length: u8) -> Self {
+    pub const fn new(lower: char, upper: char, length: u32) -> Self {
         SearchSpecification {
             lower,
             upper,
             length,
         }
     }
+
+    /// Ensure the result type is wide enough for this specification
+    fn check_width<T: SearchResult>(&self) -> Result<(), Error> {
+        match self.length <= T::BITS {
+            true => Ok(()),
+            false => Err(anyhow!(
+                "Specification {:?} is too long for a {}-bit number",
+                self,
+                T::BITS
+            )),
+        }
+    }
+
     /// Parse the binary search specification into a binary number
     ///
     /// This is great. You can directly convert the search spec into a binary
@@ -28,22 +73,49 @@ impl SearchSpecification {
     /// from 0 to 7, if we want the last row that's RRR (111 == 7). If we want
     /// the first row it's LLL (000 == 0). If we want the second row it's
     /// LLR (001 == 1), and so on.
-    fn parse(&self, search: &str) -> Result<u8, Error> {
-        if search.len() != self.length.into() {
-            return Err(anyhow!(
-                "Invalid length search {} for specification {:?}",
-                search,
-                self
-            ));
+    ///
+    /// The result can be any unsigned integer with at least as many bits as
+    /// the specification is long.
+    pub fn parse<T: SearchResult>(&self, search: &str) -> Result<T, Error> {
+        self.check_width::<T>()?;
+        if search.chars().count() != self.length as usize {
+            return Err(ParseError::Malformed {
+                at: Location::of(search, search),
+                what: "search".into(),
+                expected: format!("{} characters", self.length),
+            }
+            .into());
         }
         search
-            .chars()
-            .enumerate()
-            .try_fold(0u8, |acc, (idx, c)| match c {
-                _ if c == self.lower => Ok(acc),
-                _ if c == self.upper => Ok(acc | 1 << (self.length - 1) - idx as u8),
-                _ => Err(anyhow!("Invalid character in search value: {}", search)),
+            .char_indices()
+            .try_fold(T::from(0), |acc, (idx, c)| match c {
+                // shift what we've got so far up a bit to make room for this one
+                _ if c == self.lower => Ok(acc << 1),
+                _ if c == self.upper => Ok(acc << 1 | T::from(1)),
+                _ => Err(self.invalid_char(search, idx, c).into()),
+            })
+    }
+
+    fn invalid_char(&self, input: &str, idx: usize, c: char) -> ParseError {
+        ParseError::InvalidChar {
+            at: Location::at(input, idx, c.len_utf8()),
+            expected: format!("{:?} or {:?}", self.lower, self.upper),
+        }
+    }
+
+    /// The inverse of `parse`, turning a number back into a search
+    pub fn encode<T: SearchResult>(&self, value: T) -> Result<String, Error> {
+        self.check_width::<T>()?;
+        if self.length < T::BITS && value >> self.length != T::from(0) {
+            return Err(anyhow!("Value is too large for specification {:?}", self));
+        }
+        Ok((0..self.length)
+            .rev()
+            .map(|bit| match value >> bit & T::from(1) == T::from(1) {
+                true => self.upper,
+                false => self.lower,
             })
+            .collect())
     }
 }
 
@@ -66,75 +138,658 @@ mod test_search_spec {
             ("BBB", 7),
         ];
         cases.iter().for_each(|case| {
-            assert_eq!(spec.parse(case.0).unwrap(), case.1);
+            assert_eq!(spec.parse::<u8>(case.0).unwrap(), case.1);
         });
+        let err = spec.parse::<u8>("ABBA").unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ParseError>().unwrap().to_string(),
+            "Malformed search \"ABBA\" at line 1, column 1, expected 3 characters"
+        );
+    }
+
+    #[test]
+    fn test_wide_specifications() {
+        let spec = SearchSpecification::new('0', '1', 40);
+        let search = "1000000000000000000000000000000000000001";
+        let value = spec.parse::<u64>(search).unwrap();
+        assert_eq!(value, (1 << 39) + 1);
+        assert_eq!(spec.encode(value).unwrap(), search);
+        assert!(spec.parse::<u32>(search).is_err());
+        assert!(spec.encode(1u64 << 40).is_err());
+    }
+
+    #[test]
+    fn test_encode_is_inverse_of_parse() {
+        let spec = SearchSpecification::new('F', 'B', 7);
+        (0..128u16).for_each(|row| {
+            let search = spec.encode(row).unwrap();
+            assert_eq!(spec.parse::<u16>(&search).unwrap(), row);
+        });
+        assert_eq!(spec.encode(44u8).unwrap(), "FBFBBFF");
     }
 }
 
-#[derive(Debug)]
-struct Seat {
-    row: u8,
-    col: u8,
+/// The number of bits needed to count from 0 to n - 1
+const fn bits_needed(n: u32) -> u32 {
+    match n {
+        0 | 1 => 0,
+        _ => u32::BITS - (n - 1).leading_zeros(),
+    }
 }
-impl Seat {
-    const ROW_SEARCH_SPEC: SearchSpecification = SearchSpecification::new('F', 'B', 7);
-    const COL_SEARCH_SPEC: SearchSpecification = SearchSpecification::new('L', 'R', 3);
 
-    fn id(&self) -> usize {
-        self.row as usize * 8 + self.col as usize
+/// The shape of a plane, and how its boarding passes are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AircraftLayout {
+    rows: u32,
+    columns: u32,
+    row_spec: SearchSpecification,
+    col_spec: SearchSpecification,
+}
+impl AircraftLayout {
+    /// The plane from the puzzle, with 128 rows picked out by F/B and 8
+    /// columns picked out by L/R
+    pub const STANDARD: AircraftLayout =
+        AircraftLayout::new_unchecked(128, ('F', 'B'), 8, ('L', 'R'));
+
+    const fn new_unchecked(
+        rows: u32,
+        (row_lower, row_upper): (char, char),
+        columns: u32,
+        (col_lower, col_upper): (char, char),
+    ) -> Self {
+        AircraftLayout {
+            rows,
+            columns,
+            row_spec: SearchSpecification::new(row_lower, row_upper, bits_needed(rows)),
+            col_spec: SearchSpecification::new(
+                col_lower,
+                col_upper,
+                bits_needed(columns),
+            ),
+        }
+    }
+
+    /// A plane with the given number of rows and columns, where the letters
+    /// are the (lower, upper) halves for the binary search along each. Row
+    /// and column counts don't need to be powers of two.
+    pub fn new(
+        rows: u32,
+        row_letters: (char, char),
+        columns: u32,
+        column_letters: (char, char),
+    ) -> Result<Self, Error> {
+        if rows == 0 || columns == 0 {
+            return Err(anyhow!("A plane needs at least one row and one column"));
+        }
+        let letters = [
+            row_letters.0,
+            row_letters.1,
+            column_letters.0,
+            column_letters.1,
+        ];
+        // each letter needs to mean one thing, or we can't tell where the
+        // row search stops
+        if (1..letters.len()).any(|i| letters[i..].contains(&letters[i - 1])) {
+            return Err(anyhow!(
+                "Boarding pass letters {:?} must be unique",
+                letters
+            ));
+        }
+        Ok(Self::new_unchecked(
+            rows,
+            row_letters,
+            columns,
+            column_letters,
+        ))
+    }
+
+    /// How many seats there are in the plane, which is one more than the
+    /// largest seat ID
+    pub fn seats(&self) -> usize {
+        self.rows as usize * self.columns as usize
+    }
+
+    /// The length of a boarding pass for this plane
+    pub fn pass_length(&self) -> usize {
+        (self.row_spec.length + self.col_spec.length) as usize
+    }
+
+    pub fn rows(&self) -> u32 {
+        self.rows
+    }
+
+    pub fn columns(&self) -> u32 {
+        self.columns
     }
 }
-impl TryFrom<&str> for Seat {
-    type Error = Error;
 
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        let value = value.trim();
-        let row_search = value.get(0..7).ok_or(anyhow!("invalid input {}", &value))?;
-        let col_search = value
-            .get(7..10)
-            .ok_or(anyhow!("invalid input {}", &value))?;
+/// A seat on a plane, which is always inside the plane's layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Seat {
+    row: u32,
+    col: u32,
+    layout: AircraftLayout,
+}
+impl Seat {
+    /// Parse a boarding pass for a plane with the given layout
+    pub fn parse(value: &str, layout: &AircraftLayout) -> Result<Self, ParseError> {
+        let pass = value.trim();
+        if pass.chars().count() != layout.pass_length() {
+            return Err(ParseError::Malformed {
+                at: Location::of(value, pass),
+                what: "boarding pass".into(),
+                expected: format!("{} characters", layout.pass_length()),
+            });
+        }
+        // the row search is first, then the column search
+        let split = pass
+            .char_indices()
+            .nth(layout.row_spec.length as usize)
+            .map_or(pass.len(), |(idx, _)| idx);
+        if let Some((idx, c, spec)) = pass.char_indices().find_map(|(idx, c)| {
+            let spec = match idx < split {
+                true => layout.row_spec,
+                false => layout.col_spec,
+            };
+            (c != spec.lower && c != spec.upper).then_some((idx, c, spec))
+        }) {
+            let offset = pass.as_ptr() as usize - value.as_ptr() as usize;
+            return Err(spec.invalid_char(value, offset + idx, c));
+        }
+        let (row_search, col_search) = pass.split_at(split);
         // The trick here is the search specifications themselves are the SAME
         // as the binary representation of the column/row number. So LLL is 000,
         // corresponding to row 0, RRR is 111 corresponding to row 7, LLR is 001
-        // corresponding to row 1, and so on.
+        // corresponding to row 1, and so on. The characters were checked
+        // above, and the layout's specifications always fit in a u32.
+        let decode = |spec: SearchSpecification, search| {
+            spec.parse::<u32>(search)
+                .expect("the pass was checked against the layout")
+        };
+        let row = decode(layout.row_spec, row_search);
+        let col = decode(layout.col_spec, col_search);
+        // the searches can point past the end of planes whose size isn't a
+        // power of two
+        if row >= layout.rows || col >= layout.columns {
+            return Err(ParseError::Invalid {
+                at: Location::of(value, pass),
+                what: "seat for this plane".into(),
+            });
+        }
         Ok(Self {
-            row: Seat::ROW_SEARCH_SPEC.parse(row_search)?,
-            col: Seat::COL_SEARCH_SPEC.parse(col_search)?,
+            row,
+            col,
+            layout: *layout,
         })
     }
+
+    /// The seat at the given row and column of a plane
+    pub fn from_row_col(
+        row: u32,
+        col: u32,
+        layout: &AircraftLayout,
+    ) -> Result<Self, Error> {
+        match row < layout.rows && col < layout.columns {
+            true => Ok(Self {
+                row,
+                col,
+                layout: *layout,
+            }),
+            false => Err(anyhow!("There is no seat at row {}, column {}", row, col)),
+        }
+    }
+
+    /// The seat with the given ID on a plane
+    pub fn from_id(id: usize, layout: &AircraftLayout) -> Result<Self, Error> {
+        let columns = layout.columns as usize;
+        match id < layout.seats() {
+            true => {
+                Self::from_row_col((id / columns) as u32, (id % columns) as u32, layout)
+            }
+            false => Err(anyhow!("There is no seat with ID {}", id)),
+        }
+    }
+
+    pub fn row(&self) -> u32 {
+        self.row
+    }
+
+    pub fn col(&self) -> u32 {
+        self.col
+    }
+
+    pub fn id(&self) -> usize {
+        self.row as usize * self.layout.columns as usize + self.col as usize
+    }
 }
+impl TryFrom<&str> for Seat {
+    type Error = ParseError;
 
-pub fn day_five_solution_one() -> Result<usize, Error> {
-    INPUT.lines().try_fold(0, |acc, ln| {
-        Seat::try_from(ln).map(|seat| max(acc, seat.id()))
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Seat::parse(value, &AircraftLayout::STANDARD)
+    }
+}
+impl fmt::Display for Seat {
+    /// Write the seat's boarding pass, e.g. `FBFBBFFRLR`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // a seat's row and column always fit in its layout's specs
+        let row = self
+            .layout
+            .row_spec
+            .encode(self.row)
+            .map_err(|_| fmt::Error)?;
+        let col = self
+            .layout
+            .col_spec
+            .encode(self.col)
+            .map_err(|_| fmt::Error)?;
+        write!(f, "{}{}", row, col)
+    }
+}
+
+/// Generate a shuffled file of `count` boarding passes, one per line, for a
+/// run of seats in the plane except for the seat with the `missing` ID
+///
+/// Like the puzzle, the seats before and after the run are left out, but
+/// the seats either side of the missing one are always there.
+pub fn generate_passes<R: Rng>(
+    layout: &AircraftLayout,
+    count: usize,
+    missing: usize,
+    rng: &mut R,
+) -> Result<String, Error> {
+    if missing == 0 || missing + 1 >= layout.seats() {
+        return Err(anyhow!(
+            "Seat {} needs a seat on either side of it",
+            missing
+        ));
+    }
+    if count < 2 || count >= layout.seats() {
+        return Err(anyhow!(
+            "Can't fit {} passes and a gap in a plane of {} seats",
+            count,
+            layout.seats()
+        ));
+    }
+    // the run is `count` seats plus the missing one, somewhere in the plane
+    let earliest = (missing + 1).saturating_sub(count);
+    let latest = (missing - 1).min(layout.seats() - 1 - count);
+    let first = rng.gen_range(earliest..=latest);
+    let last = first + count;
+    let mut ids = (first..=last)
+        .filter(|id| *id != missing)
+        .collect::<Vec<usize>>();
+    ids.shuffle(rng);
+    ids.into_iter().try_fold(String::new(), |mut out, id| {
+        out.push_str(&Seat::from_id(id, layout)?.to_string());
+        out.push('\n');
+        Ok(out)
     })
 }
 
-pub fn day_five_solution_two() -> Result<usize, Error> {
-    let mut ids = INPUT
-        .lines()
-        .map(Seat::try_from)
-        .map(|seat| seat.map(|seat| seat.id()))
-        .collect::<Result<Vec<usize>, Error>>()?;
-    ids.sort();
-    Ok(ids
-        .into_iter()
-        // We're using Result<Ok, Err> here more like an Either<L, R>, where
-        // our "error" is just to indicate that we should short-circuit since
-        // we've found our answer
-        .try_fold(0, |prev, next| {
-            if prev == 0 {
-                // Ignore the first one b/c we know that it's not at the
-                // beginning or end of the sorted list
-                return Ok(next);
-            }
+#[cfg(test)]
+mod test_seat {
+    use super::*;
 
-            // We know the target ID is missing, and both +1 and -1 are present,
-            // so if next is 2 larger than prev, we must be in the middle.
-            if next == prev + 2 {
-                return Err(next - 1);
+    #[test]
+    fn test_puzzle_seats() {
+        let cases = [
+            ("FBFBBFFRLR", 44, 5, 357),
+            ("BFFFBBFRRR", 70, 7, 567),
+            ("FFFBBBFRRR", 14, 7, 119),
+            ("BBFFBBFRLL", 102, 4, 820),
+        ];
+        cases.iter().for_each(|(pass, row, col, id)| {
+            let seat = Seat::try_from(*pass).unwrap();
+            assert_eq!((seat.row(), seat.col(), seat.id()), (*row, *col, *id));
+        });
+    }
+
+    #[test]
+    fn test_seats_round_trip() {
+        let layout = AircraftLayout::new(5, ('a', 'b'), 3, ('x', 'y')).unwrap();
+        (0..layout.seats()).for_each(|id| {
+            let seat = Seat::from_id(id, &layout).unwrap();
+            assert_eq!(seat.id(), id);
+            assert_eq!(Seat::parse(&seat.to_string(), &layout).unwrap(), seat);
+        });
+        assert!(Seat::from_id(15, &layout).is_err());
+        assert!(Seat::from_row_col(0, 3, &layout).is_err());
+        let seat = Seat::from_row_col(44, 5, &AircraftLayout::STANDARD).unwrap();
+        assert_eq!(seat.to_string(), "FBFBBFFRLR");
+    }
+
+    #[test]
+    fn test_generated_passes() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let layout = AircraftLayout::STANDARD;
+        let passes =
+            generate_passes(&layout, 700, 600, &mut StdRng::seed_from_u64(5)).unwrap();
+        let ids = passes
+            .lines()
+            .map(|ln| Seat::parse(ln, &layout).map(|s| s.id()))
+            .collect::<Result<Vec<_>, ParseError>>()
+            .unwrap();
+        assert_eq!(ids.len(), 700);
+        assert_eq!(find_missing_seats(ids), MissingSeat::One(600));
+        // the same seed gives the same file
+        assert_eq!(
+            generate_passes(&layout, 700, 600, &mut StdRng::seed_from_u64(5)).unwrap(),
+            passes
+        );
+        let rng = &mut StdRng::seed_from_u64(5);
+        assert!(generate_passes(&layout, 700, 0, rng).is_err());
+        assert!(generate_passes(&layout, 1, 600, rng).is_err());
+        assert!(generate_passes(&layout, 1024, 600, rng).is_err());
+        // the smallest and largest runs still fit around the gap
+        let ids = |passes: String| {
+            passes
+                .lines()
+                .map(|ln| Seat::parse(ln, &layout).unwrap().id())
+                .collect::<Vec<_>>()
+        };
+        let smallest = ids(generate_passes(&layout, 2, 1, rng).unwrap());
+        assert_eq!(find_missing_seats(smallest), MissingSeat::One(1));
+        let largest = ids(generate_passes(&layout, 1023, 1022, rng).unwrap());
+        assert_eq!(find_missing_seats(largest), MissingSeat::One(1022));
+    }
+
+    #[test]
+    fn test_custom_layout() {
+        // 5 rows need 3 letters, 3 columns need 2
+        let layout = AircraftLayout::new(5, ('a', 'b'), 3, ('x', 'y')).unwrap();
+        let seat = Seat::parse("baayx", &layout).unwrap();
+        assert_eq!((seat.row(), seat.col(), seat.id()), (4, 2, 14));
+        assert_eq!(
+            Seat::parse("bayyx", &layout).err().unwrap().to_string(),
+            "Invalid character \"y\" at line 1, column 3, expected 'a' or 'b'"
+        );
+        assert_eq!(
+            Seat::parse("bbbxx", &layout).err().unwrap().to_string(),
+            "Invalid seat for this plane \"bbbxx\" at line 1, column 1"
+        );
+        assert_eq!(layout.seats(), 15);
+        // row 5 and column 3 don't exist
+        assert!(Seat::parse("babxx", &layout).is_err());
+        assert!(Seat::parse("aaayy", &layout).is_err());
+        assert!(Seat::parse("FBFBBFFRLR", &layout).is_err());
+        assert!(AircraftLayout::new(5, ('a', 'b'), 3, ('b', 'c')).is_err());
+    }
+}
+
+/// Which seats on a plane are taken, built up from boarding passes
+#[derive(Debug, Clone)]
+pub struct SeatMap {
+    layout: AircraftLayout,
+    // indexed by seat ID
+    occupied: Vec<bool>,
+    duplicates: Vec<Seat>,
+}
+impl SeatMap {
+    /// An empty plane
+    pub fn new(layout: AircraftLayout) -> Self {
+        Self {
+            layout,
+            occupied: vec![false; layout.seats()],
+            duplicates: Vec::new(),
+        }
+    }
+
+    /// Fill a plane from boarding passes, one per line
+    pub fn from_passes(input: &str, layout: AircraftLayout) -> Result<Self, Error> {
+        parse_lines(input, |ln| Seat::parse(ln, &layout))?
+            .into_iter()
+            .try_fold(Self::new(layout), |mut map, seat| {
+                map.add(seat)?;
+                Ok(map)
+            })
+    }
+
+    /// Mark a seat as occupied. If it already was, someone has a duplicate
+    /// boarding pass, which we keep track of.
+    pub fn add(&mut self, seat: Seat) -> Result<(), Error> {
+        if seat.layout != self.layout {
+            return Err(anyhow!("Seat {:?} is on a different plane", seat));
+        }
+        let id = seat.id();
+        match self.occupied.get_mut(id) {
+            Some(true) => self.duplicates.push(seat),
+            Some(occupied) => *occupied = true,
+            None => {
+                return Err(anyhow!("There is no seat with ID {} on this plane", id))
             }
-            Ok(next)
+        }
+        Ok(())
+    }
+
+    pub fn seat(&self, id: usize) -> Seat {
+        Seat::from_id(id, &self.layout).expect("the map has a slot for every seat")
+    }
+
+    pub fn is_occupied(&self, row: u32, col: u32) -> bool {
+        row < self.layout.rows
+            && col < self.layout.columns
+            && self.occupied[row as usize * self.layout.columns as usize + col as usize]
+    }
+
+    /// Every seat nobody has a boarding pass for
+    pub fn empty_seats(&self) -> impl Iterator<Item = Seat> + '_ {
+        self.occupied
+            .iter()
+            .enumerate()
+            .filter(|(_, occupied)| !**occupied)
+            .map(move |(id, _)| self.seat(id))
+    }
+
+    /// Empty seats where the seats with the IDs on either side are occupied.
+    /// Like IDs, this wraps from the end of one row to the start of the next.
+    pub fn empty_seats_with_occupied_neighbours(
+        &self,
+    ) -> impl Iterator<Item = Seat> + '_ {
+        self.occupied
+            .windows(3)
+            .enumerate()
+            .filter(|(_, window)| matches!(window, [true, false, true]))
+            .map(move |(id, _)| self.seat(id + 1))
+    }
+
+    /// Seats that more than one boarding pass pointed to, once for each
+    /// extra pass
+    pub fn duplicates(&self) -> &[Seat] {
+        &self.duplicates
+    }
+}
+impl fmt::Display for SeatMap {
+    /// Draw the plane, one row per line, with `#` for occupied seats and `.`
+    /// for empty ones, and the row number on the left
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = (self.layout.rows - 1).to_string().len();
+        (0..self.layout.rows).try_for_each(|row| {
+            let seats = (0..self.layout.columns)
+                .map(|col| match self.is_occupied(row, col) {
+                    true => '#',
+                    false => '.',
+                })
+                .collect::<String>();
+            writeln!(f, "{:>width$} {}", row, seats, width = width)
         })
-        .unwrap_err())
+    }
+}
+
+#[cfg(test)]
+mod test_seat_map {
+    use super::*;
+
+    #[test]
+    fn test_seat_map_queries() {
+        let layout = AircraftLayout::new(3, ('F', 'B'), 3, ('L', 'R')).unwrap();
+        // seats 0, 1, 3, 5 and 6 are taken, with 6 taken twice
+        let passes = "FFLL\nFFLR\nFBLL\nFBRL\nBFLL\nBFLL\n";
+        let map = SeatMap::from_passes(passes, layout).unwrap();
+        assert_eq!(map.to_string(), "0 ##.\n1 #.#\n2 #..\n");
+        assert_eq!(
+            map.empty_seats().map(|s| s.id()).collect::<Vec<_>>(),
+            vec![2, 4, 7, 8]
+        );
+        assert_eq!(
+            map.empty_seats_with_occupied_neighbours()
+                .map(|s| s.id())
+                .collect::<Vec<_>>(),
+            vec![2, 4]
+        );
+        assert_eq!(
+            map.duplicates().iter().map(|s| s.id()).collect::<Vec<_>>(),
+            vec![6]
+        );
+    }
+}
+
+/// The result of looking for our seat among everyone else's
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MissingSeat {
+    /// Every seat with an occupied seat on each side is occupied
+    None,
+    One(usize),
+    /// More than one empty seat has occupied seats on each side, in order
+    Multiple(Vec<usize>),
+}
+
+/// Find the empty seat IDs that have an occupied seat on either side
+///
+/// This doesn't sort the IDs. Instead it marks each one in a bitset
+/// spanning the lowest to the highest ID and scans that for gaps, so it's
+/// linear in the number of IDs plus the size of that span. IDs that are too
+/// spread out for that, where the bitset would have more words than there
+/// are IDs, go in a hash set instead, and each one is checked for a gap
+/// after it. Either way it's linear in the number of IDs. Duplicate IDs are
+/// fine.
+pub fn find_missing_seats<I: IntoIterator<Item = usize>>(ids: I) -> MissingSeat {
+    let ids = ids.into_iter().collect::<Vec<usize>>();
+    let (min, max) = match (ids.iter().min(), ids.iter().max()) {
+        (Some(min), Some(max)) => (*min, *max),
+        _ => return MissingSeat::None,
+    };
+    let words = (max - min) / 64 + 1;
+    let mut gaps = match words > ids.len() {
+        true => hashed_gaps(&ids),
+        false => bitset_gaps(&ids, min, max, words),
+    };
+    match gaps.len() {
+        0 => MissingSeat::None,
+        1 => MissingSeat::One(gaps.remove(0)),
+        _ => MissingSeat::Multiple(gaps),
+    }
+}
+
+/// The gaps between `ids`, marked in a bitset of `words` words from `min`
+fn bitset_gaps(ids: &[usize], min: usize, max: usize, words: usize) -> Vec<usize> {
+    let mut taken = vec![0u64; words];
+    ids.iter()
+        .for_each(|id| taken[(id - min) / 64] |= 1 << ((id - min) % 64));
+    let is_taken = |id: usize| taken[(id - min) / 64] & 1 << ((id - min) % 64) != 0;
+
+    // the lowest and highest IDs are taken, so gaps can only be between them
+    (min + 1..max)
+        .filter(|id| !is_taken(*id) && is_taken(id - 1) && is_taken(id + 1))
+        .collect()
+}
+
+/// The gaps between `ids`, found by looking each one's neighbours up in a
+/// hash set
+fn hashed_gaps(ids: &[usize]) -> Vec<usize> {
+    let taken = ids.iter().copied().collect::<HashSet<usize>>();
+    let mut gaps = taken
+        .iter()
+        .filter_map(|id| Some((id.checked_add(1)?, id.checked_add(2)?)))
+        .filter(|(gap, next)| !taken.contains(gap) && taken.contains(next))
+        .map(|(gap, _)| gap)
+        .collect::<Vec<usize>>();
+    // there's only more than one gap when something's wrong, so putting
+    // them in order costs next to nothing
+    gaps.sort_unstable();
+    gaps
+}
+
+#[cfg(test)]
+mod test_missing_seat {
+    use super::*;
+
+    #[test]
+    fn test_find_missing_seats() {
+        assert_eq!(find_missing_seats(vec![]), MissingSeat::None);
+        assert_eq!(find_missing_seats(vec![0, 1, 2]), MissingSeat::None);
+        // 0 is a perfectly good seat ID
+        assert_eq!(find_missing_seats(vec![2, 0, 3]), MissingSeat::One(1));
+        // 7 and 8 are both missing, so neither has two occupied neighbours
+        assert_eq!(
+            find_missing_seats(vec![100, 4, 6, 9, 10, 3, 102, 9]),
+            MissingSeat::Multiple(vec![5, 101])
+        );
+        assert_eq!(
+            find_missing_seats(vec![4, 6, 7, 8, 10]),
+            MissingSeat::Multiple(vec![5, 9])
+        );
+        // far too spread out for a bitset
+        assert_eq!(find_missing_seats(vec![0, 1 << 40]), MissingSeat::None);
+        assert_eq!(
+            find_missing_seats(vec![usize::MAX - 2, 0, usize::MAX]),
+            MissingSeat::One(usize::MAX - 1)
+        );
+        assert_eq!(
+            find_missing_seats(vec![1 << 40, 3, 1, 1 << 40, (1 << 40) + 2]),
+            MissingSeat::Multiple(vec![2, (1 << 40) + 1])
+        );
+    }
+}
+
+/// The seats from some boarding passes, one per line
+fn seats(input: &str) -> Result<Vec<Seat>, ParseError> {
+    parse_lines(input, Seat::try_from)
+}
+
+fn part_one(input: &str) -> Result<usize, Error> {
+    Ok(seats(input)?
+        .iter()
+        .fold(0, |acc, seat| max(acc, seat.id())))
+}
+
+fn part_two(input: &str) -> Result<usize, Error> {
+    let ids = seats(input)?.iter().map(Seat::id).collect::<Vec<usize>>();
+    match find_missing_seats(ids) {
+        MissingSeat::One(id) => Ok(id),
+        MissingSeat::None => Err(anyhow!("There are no empty seats")),
+        MissingSeat::Multiple(ids) => {
+            Err(anyhow!("More than one seat could be ours: {:?}", ids))
+        }
+    }
+}
+
+pub fn day_five_solution_one() -> Result<usize, Error> {
+    part_one(INPUT)
 }
+
+pub fn day_five_solution_two() -> Result<usize, Error> {
+    part_two(INPUT)
+}
+
+/// Both parts, for the runner
+pub const SOLUTIONS: [Solution; 2] = [
+    Solution {
+        day: 5,
+        part: 1,
+        input: INPUT,
+        solve: part_one,
+        examples: &[Example {
+            input: EXAMPLE,
+            answer: 820,
+        }],
+    },
+    Solution {
+        day: 5,
+        part: 2,
+        input: INPUT,
+        solve: part_two,
+        examples: &[],
+    },
+];
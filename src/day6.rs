@@ -1,79 +1,255 @@
-use std::{collections::HashSet, ops::RangeInclusive};
+use rand::{seq::SliceRandom, Rng};
 
-static INPUT: &'static str = include_str!("day6.input");
+use crate::error::ParseError;
+use crate::input::{parse_lines, records, ParseMode, Record};
+use crate::runner::{Example, Solution};
 
-struct CustomsForm {
+pub mod alphabet;
+pub mod analysis;
+pub mod answers;
+
+use alphabet::Alphabet;
+use answers::AnswerSet;
+
+const INPUT: &str = include_str!("day6.input");
+const EXAMPLE: &str = include_str!("day6.example");
+
+pub struct CustomsForm<'a> {
+    alphabet: &'a Alphabet,
     // A vec of sets, where each set are the questions one individual in the
     // group answered.
-    questions_answered_yes: Vec<HashSet<char>>,
+    questions_answered_yes: Vec<AnswerSet<'a>>,
 }
-impl CustomsForm {
-    const ALLOWED_CHARS: RangeInclusive<char> = 'a'..='z';
+impl<'a> CustomsForm<'a> {
+    /// Parse a group's answers, one person per line. In strict mode, any
+    /// answer that isn't in the alphabet is an error, which says where in
+    /// the input it was.
+    pub fn parse(
+        record: &Record,
+        alphabet: &'a Alphabet,
+        mode: ParseMode,
+    ) -> Result<Self, ParseError> {
+        Ok(CustomsForm {
+            alphabet,
+            // convert each line into a set
+            questions_answered_yes: parse_lines(record.text, |ln| {
+                alphabet.parse_answers(ln, mode)
+            })
+            .map_err(|e| record.relocate(e))?,
+        })
+    }
+
+    pub fn any_yes(&self) -> AnswerSet<'a> {
+        self.answered_by(|count| count > 0)
+    }
 
-    fn any_yes_count(&self) -> usize {
+    pub fn any_yes_count(&self) -> usize {
         // the length of the union set of yes answers from all members of the
         // group
-        self.questions_answered_yes
-            .iter()
-            .fold(HashSet::new(), |acc, set| {
-                acc.union(set).map(|i| *i).collect::<HashSet<char>>()
-            })
-            .len()
+        self.any_yes().len()
     }
 
-    fn every_yes_count(&self) -> usize {
+    pub fn every_yes_count(&self) -> usize {
         // the length of the intersection set of yes answers from all members
-        // of the group
-        let accumulator: Option<HashSet<char>> = None;
+        // of the group. If nobody's in the group, nobody said yes.
+        let group_size = self.questions_answered_yes.len();
+        self.answered_by(|count| count > 0 && count == group_size)
+            .len()
+    }
+
+    /// How many people in the group answered yes to each question, for every
+    /// question in alphabet order
+    pub fn question_counts(&self) -> Vec<(&'a str, usize)> {
+        self.alphabet
+            .questions()
+            .enumerate()
+            .map(|(idx, q)| {
+                let count = self
+                    .questions_answered_yes
+                    .iter()
+                    .filter(|set| set.contains_index(idx))
+                    .count();
+                (q, count)
+            })
+            .collect()
+    }
+
+    /// The questions whose count matches the predicate
+    pub fn answered_by<F: Fn(usize) -> bool>(&self, predicate: F) -> AnswerSet<'a> {
+        AnswerSet::from_questions(
+            self.alphabet,
+            self.question_counts()
+                .into_iter()
+                .filter(|(_, count)| predicate(*count))
+                .map(|(q, _)| q),
+        )
+    }
+
+    /// The questions that exactly `k` people answered yes to
+    pub fn answered_by_exactly(&self, k: usize) -> AnswerSet<'a> {
+        self.answered_by(|count| count == k)
+    }
+
+    /// The questions that more than half of the group answered yes to
+    pub fn answered_by_majority(&self) -> AnswerSet<'a> {
+        let group_size = self.questions_answered_yes.len();
+        self.answered_by(|count| count * 2 > group_size)
+    }
+
+    /// The questions that nobody in the group answered yes to
+    pub fn answered_by_nobody(&self) -> AnswerSet<'a> {
+        self.answered_by_exactly(0)
+    }
+
+    /// The index of the person who answered yes to the most questions, and
+    /// how many that was. Ties go to whoever came first.
+    pub fn most_answers(&self) -> Option<(usize, usize)> {
+        self.answer_counts().rev().max_by_key(|(_, count)| *count)
+    }
+
+    /// The index of the person who answered yes to the fewest questions, and
+    /// how many that was. Ties go to whoever came first.
+    pub fn least_answers(&self) -> Option<(usize, usize)> {
+        self.answer_counts().min_by_key(|(_, count)| *count)
+    }
+
+    fn answer_counts(&self) -> impl DoubleEndedIterator<Item = (usize, usize)> + '_ {
         self.questions_answered_yes
             .iter()
-            .fold(accumulator, |acc, set| match acc {
-                Some(last_set) => Some(
-                    last_set
-                        .intersection(set)
-                        .map(|i| *i)
-                        .collect::<HashSet<char>>(),
-                ),
-                None => Some(set.clone()),
-            })
-            // we assume there will be at least one answer from one person
-            .unwrap()
-            .len()
+            .map(|set| set.len())
+            .enumerate()
     }
 }
 // We have no reason to assume the input might be bad, so we'll treat it
-// as infallible and use From
-impl From<&str> for CustomsForm {
+// as infallible and use From, dropping anything that isn't a-z
+impl From<&str> for CustomsForm<'static> {
     fn from(value: &str) -> Self {
-        CustomsForm {
-            questions_answered_yes: value
-                .lines()
-                // convert each line into a set
-                .map(|ln| {
-                    ln.chars()
-                        .filter(|c| CustomsForm::ALLOWED_CHARS.contains(c))
-                        .fold(HashSet::new(), |mut acc, c| {
-                            acc.insert(c);
-                            acc
-                        })
-                })
-                .collect(),
-        }
+        let record = Record {
+            text: value,
+            span: 0..value.len(),
+            lines: 1..value.lines().count() + 1,
+        };
+        CustomsForm::parse(&record, Alphabet::lowercase(), ParseMode::Lenient)
+            .expect("lenient parsing never fails")
     }
 }
 
-pub fn day_six_solution_one() -> usize {
-    INPUT
-        .split("\n\n")
-        .map(CustomsForm::from)
+/// Generate `count` groups of customs forms, one person per line
+///
+/// Each group has a few questions in common that most people in it answer
+/// yes to, and the odd other question here and there.
+pub fn generate_customs_forms<R: Rng>(count: usize, rng: &mut R) -> String {
+    let questions = ('a'..='z').collect::<Vec<char>>();
+    let groups = (0..count).map(|_| {
+        let amount = rng.gen_range(1..=8);
+        let common = questions
+            .choose_multiple(rng, amount)
+            .copied()
+            .collect::<Vec<char>>();
+        let people = (0..rng.gen_range(1..=5)).map(|_| {
+            let mut answers = questions
+                .iter()
+                .filter(|q| match common.contains(q) {
+                    true => rng.gen_bool(0.8),
+                    false => rng.gen_bool(0.05),
+                })
+                .copied()
+                .collect::<Vec<char>>();
+            // nobody hands in a blank form
+            if answers.is_empty() {
+                answers.push(common[0]);
+            }
+            answers.shuffle(rng);
+            answers.into_iter().collect::<String>()
+        });
+        people.collect::<Vec<_>>().join("\n")
+    });
+    groups.collect::<Vec<_>>().join("\n\n") + "\n"
+}
+
+fn part_one(input: &str) -> usize {
+    records(input)
+        .map(|record| CustomsForm::from(record.text))
         .map(|form| form.any_yes_count())
         .sum()
 }
 
-pub fn day_six_solution_two() -> usize {
-    INPUT
-        .split("\n\n")
-        .map(CustomsForm::from)
+fn part_two(input: &str) -> usize {
+    records(input)
+        .map(|record| CustomsForm::from(record.text))
         .map(|form| form.every_yes_count())
         .sum()
 }
+
+pub fn day_six_solution_one() -> usize {
+    part_one(INPUT)
+}
+
+pub fn day_six_solution_two() -> usize {
+    part_two(INPUT)
+}
+
+/// Both parts, for the runner
+pub const SOLUTIONS: [Solution; 2] = [
+    Solution {
+        day: 6,
+        part: 1,
+        input: INPUT,
+        solve: |input| Ok(part_one(input)),
+        examples: &[Example {
+            input: EXAMPLE,
+            answer: 11,
+        }],
+    },
+    Solution {
+        day: 6,
+        part: 2,
+        input: INPUT,
+        solve: |input| Ok(part_two(input)),
+        examples: &[Example {
+            input: EXAMPLE,
+            answer: 6,
+        }],
+    },
+];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn group_statistics() {
+        let form = CustomsForm::from("abc\nab\nb\nxyz");
+        assert_eq!(form.any_yes_count(), 6);
+        assert_eq!(form.every_yes_count(), 0);
+        assert_eq!(form.answered_by_exactly(1).to_string(), "cxyz");
+        assert_eq!(form.answered_by_exactly(3).to_string(), "b");
+        assert_eq!(form.answered_by_majority().to_string(), "b");
+        assert_eq!(form.answered_by_nobody().len(), 20);
+        assert_eq!(
+            &form.question_counts()[..3],
+            &[("a", 2), ("b", 3), ("c", 1)]
+        );
+        assert_eq!(form.most_answers(), Some((0, 3)));
+        assert_eq!(form.least_answers(), Some((2, 1)));
+        assert_eq!(CustomsForm::from("").most_answers(), None);
+    }
+
+    #[test]
+    fn strict_parsing_reports_locations() {
+        let input = "ab\nc\n\nde\ndF\n";
+        let alphabet = Alphabet::lowercase();
+        let forms = records(input)
+            .map(|record| CustomsForm::parse(&record, alphabet, ParseMode::Strict))
+            .collect::<Vec<_>>();
+        assert_eq!(forms[0].as_ref().unwrap().any_yes_count(), 3);
+        assert_eq!(
+            forms[1].as_ref().err().unwrap().to_string(),
+            "Invalid question \"F\" at line 5, column 2"
+        );
+        let upper = Alphabet::from_chars(('a'..='z').chain('A'..='Z')).unwrap();
+        let record = records(input).nth(1).unwrap();
+        let form = CustomsForm::parse(&record, &upper, ParseMode::Strict).unwrap();
+        assert_eq!(form.any_yes().to_string(), "deF");
+    }
+}
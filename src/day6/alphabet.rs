@@ -0,0 +1,218 @@
+use anyhow::{anyhow, Error};
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+
+use super::answers::AnswerSet;
+use crate::error::{Location, ParseError};
+use crate::input::ParseMode;
+
+lazy_static! {
+    static ref LOWERCASE: Alphabet =
+        Alphabet::from_chars('a'..='z').expect("a-z is a valid alphabet");
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum QuestionKind {
+    /// Each character on a line is a question
+    Chars,
+    /// Questions are words, separated by whitespace or commas
+    Words,
+}
+
+/// The questions on a customs form
+///
+/// Questions are either single characters, in which case a person's answers
+/// are written like `abx`, or longer IDs, in which case they're separated
+/// by whitespace or commas, like `q1 q2,q10`. There can be at most
+/// `Alphabet::MAX_QUESTIONS` questions.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Alphabet {
+    questions: Vec<String>,
+    indices: HashMap<String, usize>,
+    kind: QuestionKind,
+}
+impl Alphabet {
+    /// The most questions an alphabet can have, limited by `AnswerSet`
+    pub const MAX_QUESTIONS: usize = AnswerSet::CAPACITY;
+
+    fn new(questions: Vec<String>, kind: QuestionKind) -> Result<Self, Error> {
+        if questions.is_empty() {
+            return Err(anyhow!("An alphabet needs at least one question"));
+        }
+        if questions.len() > Self::MAX_QUESTIONS {
+            return Err(anyhow!(
+                "An alphabet can have at most {} questions, not {}",
+                Self::MAX_QUESTIONS,
+                questions.len()
+            ));
+        }
+        let mut indices = HashMap::new();
+        for (idx, question) in questions.iter().enumerate() {
+            if indices.insert(question.clone(), idx).is_some() {
+                return Err(anyhow!("Question {} appears more than once", question));
+            }
+        }
+        Ok(Self {
+            questions,
+            indices,
+            kind,
+        })
+    }
+
+    /// The puzzle's alphabet, `a` through `z`
+    pub fn lowercase() -> &'static Alphabet {
+        &LOWERCASE
+    }
+
+    /// An alphabet of single-character questions, e.g.
+    /// `Alphabet::from_chars(('a'..='z').chain('0'..='9'))`
+    pub fn from_chars<I: IntoIterator<Item = char>>(chars: I) -> Result<Self, Error> {
+        let questions = chars.into_iter().map(String::from).collect::<Vec<_>>();
+        if let Some(q) = questions.iter().find(|q| q.trim().is_empty()) {
+            return Err(anyhow!("Question {:?} can't be whitespace", q));
+        }
+        Self::new(questions, QuestionKind::Chars)
+    }
+
+    /// An alphabet of questions with longer IDs, e.g. `["q1", "q2"]`
+    pub fn from_words<I, S>(words: I) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let questions = words.into_iter().map(Into::into).collect::<Vec<String>>();
+        let bad = |q: &String| {
+            q.is_empty() || q.contains(|c: char| c.is_whitespace() || c == ',')
+        };
+        if let Some(q) = questions.iter().find(|q| bad(q)) {
+            return Err(anyhow!("Question {:?} must be a single word", q));
+        }
+        Self::new(questions, QuestionKind::Words)
+    }
+
+    pub fn len(&self) -> usize {
+        self.questions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.questions.is_empty()
+    }
+
+    /// Whether questions are written one character each
+    pub fn is_chars(&self) -> bool {
+        self.kind == QuestionKind::Chars
+    }
+
+    /// The position of a question in the alphabet
+    pub fn index_of(&self, question: &str) -> Option<usize> {
+        self.indices.get(question).copied()
+    }
+
+    /// The question at a position in the alphabet
+    pub fn question(&self, idx: usize) -> Option<&str> {
+        self.questions.get(idx).map(String::as_str)
+    }
+
+    /// All the questions, in order
+    pub fn questions(&self) -> impl Iterator<Item = &str> {
+        self.questions.iter().map(String::as_str)
+    }
+
+    /// Split a line into answers, each a slice of the line
+    fn tokens<'l>(&self, line: &'l str) -> Vec<&'l str> {
+        let mut tokens = Vec::new();
+        let mut start: Option<usize> = None;
+        let is_separator = |c: char| match self.kind {
+            QuestionKind::Chars => c.is_whitespace(),
+            QuestionKind::Words => c.is_whitespace() || c == ',',
+        };
+        for (idx, c) in line.char_indices() {
+            if is_separator(c) {
+                if let Some(start_idx) = start.take() {
+                    tokens.push(&line[start_idx..idx]);
+                }
+                continue;
+            }
+            match self.kind {
+                QuestionKind::Chars => tokens.push(&line[idx..idx + c.len_utf8()]),
+                QuestionKind::Words => {
+                    start.get_or_insert(idx);
+                }
+            }
+        }
+        if let Some(start_idx) = start {
+            tokens.push(&line[start_idx..]);
+        }
+        tokens
+    }
+
+    /// Parse one person's answers from a line on its own
+    pub fn parse_answers(
+        &self,
+        line: &str,
+        mode: ParseMode,
+    ) -> Result<AnswerSet<'_>, ParseError> {
+        self.tokens(line).into_iter().try_fold(
+            AnswerSet::empty(self),
+            |mut set, question| match (set.insert(question), mode) {
+                (false, ParseMode::Strict) => Err(ParseError::Invalid {
+                    at: Location::of(line, question),
+                    what: "question".into(),
+                }),
+                _ => Ok(set),
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn char_alphabets() {
+        let alphabet =
+            Alphabet::from_chars(('A'..='Z').chain('0'..='9').chain("✓✗".chars()))
+                .unwrap();
+        let set = alphabet.parse_answers("AB 9✓", ParseMode::Strict).unwrap();
+        assert_eq!(set.to_string(), "AB9✓");
+        let err = alphabet
+            .parse_answers("AbC", ParseMode::Strict)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid question \"b\" at line 1, column 2"
+        );
+        let set = alphabet.parse_answers("AbC", ParseMode::Lenient).unwrap();
+        assert_eq!(set.to_string(), "AC");
+    }
+
+    #[test]
+    fn word_alphabets() {
+        let alphabet = Alphabet::from_words(vec!["q1", "q2", "q10"]).unwrap();
+        let set = alphabet
+            .parse_answers("q10,q1  q2", ParseMode::Strict)
+            .unwrap();
+        assert_eq!(set.len(), 3);
+        assert_eq!(set.to_string(), "q1 q2 q10");
+        let err = alphabet
+            .parse_answers("q1 q3", ParseMode::Strict)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid question \"q3\" at line 1, column 4"
+        );
+    }
+
+    #[test]
+    fn bad_alphabets() {
+        assert!(Alphabet::from_chars("aba".chars()).is_err());
+        assert!(Alphabet::from_chars("a b".chars()).is_err());
+        assert!(Alphabet::from_words(vec!["q 1"]).is_err());
+        assert!(Alphabet::from_words(Vec::<String>::new()).is_err());
+        assert!(Alphabet::from_chars(
+            (0..200u32).filter_map(|i| std::char::from_u32(0x100 + i))
+        )
+        .is_err());
+    }
+}
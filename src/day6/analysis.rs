@@ -0,0 +1,272 @@
+use anyhow::{anyhow, Error};
+use std::{cmp::Reverse, io};
+
+use super::alphabet::Alphabet;
+use super::answers::AnswerSet;
+use super::CustomsForm;
+
+/// How many people answered yes to each pair of questions
+///
+/// The diagonal is how many people answered yes to each question at all.
+#[derive(Debug, Clone)]
+pub struct CoOccurrence<'a> {
+    alphabet: &'a Alphabet,
+    counts: Vec<Vec<usize>>,
+}
+impl<'a> CoOccurrence<'a> {
+    /// Count co-occurrences over every person in every group
+    ///
+    /// Every group has to use `alphabet`, or an equal one.
+    pub fn from_forms(
+        alphabet: &'a Alphabet,
+        forms: &[CustomsForm<'a>],
+    ) -> Result<Self, Error> {
+        let expected = AnswerSet::empty(alphabet);
+        if let Some(idx) = forms.iter().position(|form| {
+            let mut people = form.questions_answered_yes.iter();
+            people.any(|person| !person.same_alphabet(&expected))
+        }) {
+            return Err(anyhow!("Group {} uses a different alphabet", idx));
+        }
+        let mut counts = vec![vec![0; alphabet.len()]; alphabet.len()];
+        forms
+            .iter()
+            .flat_map(|form| form.questions_answered_yes.iter())
+            .for_each(|person| {
+                let answered = (0..alphabet.len())
+                    .filter(|idx| person.contains_index(*idx))
+                    .collect::<Vec<usize>>();
+                answered.iter().for_each(|i| {
+                    answered.iter().for_each(|j| counts[*i][*j] += 1);
+                });
+            });
+        Ok(Self { alphabet, counts })
+    }
+
+    /// How many people answered yes to both questions
+    pub fn count(&self, a: &str, b: &str) -> Option<usize> {
+        Some(self.counts[self.alphabet.index_of(a)?][self.alphabet.index_of(b)?])
+    }
+
+    /// Every pair of different questions that was answered together at
+    /// least once, most common first
+    pub fn pairs(&self) -> Vec<(&'a str, &'a str, usize)> {
+        let question = |idx| self.alphabet.question(idx).unwrap_or_default();
+        let mut pairs = (0..self.counts.len())
+            .flat_map(|i| (i + 1..self.counts.len()).map(move |j| (i, j)))
+            .filter(|(i, j)| self.counts[*i][*j] > 0)
+            .map(|(i, j)| (question(i), question(j), self.counts[i][j]))
+            .collect::<Vec<_>>();
+        // stable, so ties stay in alphabet order
+        pairs.sort_by_key(|(_, _, count)| Reverse(*count));
+        pairs
+    }
+
+    /// Write the matrix as CSV, with the questions as the header row and
+    /// the first column
+    pub fn write_csv<W: io::Write>(&self, writer: W) -> Result<(), Error> {
+        write_matrix(self.alphabet.questions(), &self.counts, writer)
+    }
+}
+
+/// The Jaccard similarity of two sets of answers: how many questions are
+/// in both, over how many are in either. Two empty sets are identical.
+///
+/// Sets from different alphabets can't be compared, so give `None`.
+pub fn jaccard(a: AnswerSet, b: AnswerSet) -> Option<f64> {
+    match a.union(b)?.len() {
+        0 => Some(1.0),
+        either => Some(a.intersection(b)?.len() as f64 / either as f64),
+    }
+}
+
+/// The Jaccard similarity between every pair of groups, comparing the
+/// questions anyone in each group answered yes to
+///
+/// Every group has to use the same alphabet.
+pub fn similarity_matrix(forms: &[CustomsForm]) -> Result<Vec<Vec<f64>>, Error> {
+    let profiles = forms.iter().map(CustomsForm::any_yes).collect::<Vec<_>>();
+    profiles
+        .iter()
+        .enumerate()
+        .map(|(i, a)| {
+            profiles
+                .iter()
+                .enumerate()
+                .map(|(j, b)| {
+                    jaccard(*a, *b).ok_or_else(|| {
+                        anyhow!("Groups {} and {} use different alphabets", i, j)
+                    })
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Write a similarity matrix as CSV, with groups numbered from 0 in the
+/// header row and first column
+pub fn write_similarity_csv<W: io::Write>(
+    similarities: &[Vec<f64>],
+    writer: W,
+) -> Result<(), Error> {
+    write_matrix(
+        (0..similarities.len()).map(|idx| idx.to_string()),
+        similarities,
+        writer,
+    )
+}
+
+/// Cluster groups by their answer profiles
+///
+/// Two groups are in the same cluster if there's a chain of groups between
+/// them where each neighbour has a Jaccard similarity of at least
+/// `threshold`. Returns the cluster number of each group, where clusters are
+/// numbered from 0 in the order their first group appears. Every group has
+/// to use the same alphabet.
+pub fn cluster(forms: &[CustomsForm], threshold: f64) -> Result<Vec<usize>, Error> {
+    let similarities = similarity_matrix(forms)?;
+    // union-find, where each group points towards its cluster's root
+    let mut parents = (0..forms.len()).collect::<Vec<usize>>();
+    fn root(parents: &mut [usize], mut idx: usize) -> usize {
+        while parents[idx] != idx {
+            parents[idx] = parents[parents[idx]];
+            idx = parents[idx];
+        }
+        idx
+    }
+    for (i, row) in similarities.iter().enumerate() {
+        for (j, similarity) in row.iter().enumerate().skip(i + 1) {
+            if *similarity >= threshold {
+                let (a, b) = (root(&mut parents, i), root(&mut parents, j));
+                // keep the earliest group as the root
+                parents[a.max(b)] = a.min(b);
+            }
+        }
+    }
+    let mut roots: Vec<usize> = Vec::new();
+    Ok((0..forms.len())
+        .map(|idx| {
+            let r = root(&mut parents, idx);
+            roots.iter().position(|x| *x == r).unwrap_or_else(|| {
+                roots.push(r);
+                roots.len() - 1
+            })
+        })
+        .collect())
+}
+
+/// Write clusters as CSV, with a `group,cluster` header
+pub fn write_clusters_csv<W: io::Write>(
+    clusters: &[usize],
+    writer: W,
+) -> Result<(), Error> {
+    let mut writer = csv::Writer::from_writer(writer);
+    writer.write_record(["group", "cluster"])?;
+    clusters
+        .iter()
+        .enumerate()
+        .try_for_each(|(group, cluster)| {
+            writer.write_record([group.to_string(), cluster.to_string()])
+        })?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_matrix<I, S, T, W>(labels: I, rows: &[Vec<T>], writer: W) -> Result<(), Error>
+where
+    I: IntoIterator<Item = S>,
+    S: ToString,
+    T: ToString,
+    W: io::Write,
+{
+    let labels = labels
+        .into_iter()
+        .map(|l| l.to_string())
+        .collect::<Vec<_>>();
+    let mut writer = csv::Writer::from_writer(writer);
+    writer
+        .write_record(std::iter::once("").chain(labels.iter().map(String::as_str)))?;
+    labels.iter().zip(rows).try_for_each(|(label, row)| {
+        writer.write_record(
+            std::iter::once(label.clone()).chain(row.iter().map(ToString::to_string)),
+        )
+    })?;
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::input::{records, ParseMode};
+
+    static GROUPS: &str = "ab\nac\n\nab\nb\n\nxy\n\nxyz\n";
+
+    fn forms() -> Vec<CustomsForm<'static>> {
+        records(GROUPS)
+            .map(|record| CustomsForm::from(record.text))
+            .collect()
+    }
+
+    #[test]
+    fn co_occurrence() {
+        let matrix = CoOccurrence::from_forms(Alphabet::lowercase(), &forms()).unwrap();
+        assert_eq!(matrix.count("a", "b"), Some(2));
+        assert_eq!(matrix.count("b", "a"), Some(2));
+        assert_eq!(matrix.count("b", "b"), Some(3));
+        assert_eq!(matrix.count("a", "z"), Some(0));
+        assert_eq!(matrix.count("a", "!"), None);
+        assert_eq!(
+            &matrix.pairs()[..3],
+            &[("a", "b", 2), ("x", "y", 2), ("a", "c", 1)]
+        );
+        let mut out = Vec::new();
+        matrix.write_csv(&mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with(",a,b,c,"));
+        assert!(out.lines().nth(1).unwrap().starts_with("a,3,2,1,0,"));
+
+        let other = Alphabet::from_chars('a'..='c').unwrap();
+        let mut mixed = forms();
+        let record = records("ab\n").next().unwrap();
+        mixed.push(CustomsForm::parse(&record, &other, ParseMode::Strict).unwrap());
+        let err = CoOccurrence::from_forms(Alphabet::lowercase(), &mixed).unwrap_err();
+        assert_eq!(err.to_string(), "Group 4 uses a different alphabet");
+        assert!(CoOccurrence::from_forms(&other, &forms()).is_err());
+    }
+
+    #[test]
+    fn similarity_and_clusters() {
+        let forms = forms();
+        let similarities = similarity_matrix(&forms).unwrap();
+        assert_eq!(similarities[0][1], 2.0 / 3.0);
+        assert_eq!(similarities[2][3], 2.0 / 3.0);
+        assert_eq!(similarities[0][2], 0.0);
+        assert_eq!(similarities[1][1], 1.0);
+        assert_eq!(cluster(&forms, 0.5).unwrap(), vec![0, 0, 1, 1]);
+        assert_eq!(cluster(&forms, 0.9).unwrap(), vec![0, 1, 2, 3]);
+        assert_eq!(cluster(&forms, 0.0).unwrap(), vec![0, 0, 0, 0]);
+
+        let mut out = Vec::new();
+        write_clusters_csv(&cluster(&forms, 0.5).unwrap(), &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "group,cluster\n0,0\n1,0\n2,1\n3,1\n"
+        );
+        let mut out = Vec::new();
+        write_similarity_csv(&similarities, &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap().lines().count(), 5);
+
+        // an equal alphabet built separately compares fine, a different one
+        // doesn't compare at all
+        let same = Alphabet::from_chars('a'..='z').unwrap();
+        let other = Alphabet::from_chars('a'..='c').unwrap();
+        let record = records("ab\n").next().unwrap();
+        let mut mixed: Vec<CustomsForm> = forms;
+        mixed.push(CustomsForm::parse(&record, &same, ParseMode::Strict).unwrap());
+        assert_eq!(similarity_matrix(&mixed).unwrap()[4][0], 2.0 / 3.0);
+        mixed.push(CustomsForm::parse(&record, &other, ParseMode::Strict).unwrap());
+        assert!(similarity_matrix(&mixed).is_err());
+        assert!(cluster(&mixed, 0.5).is_err());
+    }
+}
@@ -0,0 +1,186 @@
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+};
+
+use super::alphabet::Alphabet;
+
+/// A set of questions from an alphabet, stored as one bit per question
+///
+/// Sets can only be combined with sets from an equal alphabet, so combining
+/// them gives `None` if the alphabets differ.
+#[derive(Clone, Copy)]
+pub struct AnswerSet<'a> {
+    bits: u128,
+    alphabet: &'a Alphabet,
+}
+impl<'a> AnswerSet<'a> {
+    /// The most questions a set can hold
+    pub const CAPACITY: usize = 128;
+
+    /// No questions
+    pub fn empty(alphabet: &'a Alphabet) -> Self {
+        Self { bits: 0, alphabet }
+    }
+
+    /// Every question in the alphabet
+    pub fn all(alphabet: &'a Alphabet) -> Self {
+        Self {
+            bits: match alphabet.len() {
+                Self::CAPACITY => u128::MAX,
+                len => (1 << len) - 1,
+            },
+            alphabet,
+        }
+    }
+
+    /// The set of the given questions, skipping anything that isn't one
+    pub fn from_questions<I, S>(alphabet: &'a Alphabet, questions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        questions
+            .into_iter()
+            .fold(Self::empty(alphabet), |mut set, q| {
+                set.insert(q.as_ref());
+                set
+            })
+    }
+
+    pub fn alphabet(&self) -> &'a Alphabet {
+        self.alphabet
+    }
+
+    /// Add a question to the set, returning whether it's in the alphabet
+    pub fn insert(&mut self, question: &str) -> bool {
+        self.alphabet
+            .index_of(question)
+            .map(|idx| self.bits |= 1 << idx)
+            .is_some()
+    }
+
+    pub fn contains(&self, question: &str) -> bool {
+        self.alphabet
+            .index_of(question)
+            .is_some_and(|idx| self.contains_index(idx))
+    }
+
+    /// Whether the question at the given position in the alphabet is in the
+    /// set
+    pub fn contains_index(&self, idx: usize) -> bool {
+        idx < Self::CAPACITY && self.bits & 1 << idx != 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.bits.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+
+    /// Whether the two sets' questions mean the same thing
+    pub fn same_alphabet(&self, other: &AnswerSet) -> bool {
+        std::ptr::eq(self.alphabet, other.alphabet) || self.alphabet == other.alphabet
+    }
+
+    fn combine(
+        self,
+        other: AnswerSet,
+        bits: impl Fn(u128, u128) -> u128,
+    ) -> Option<Self> {
+        match self.same_alphabet(&other) {
+            true => Some(Self {
+                bits: bits(self.bits, other.bits),
+                ..self
+            }),
+            false => None,
+        }
+    }
+
+    pub fn union(self, other: AnswerSet) -> Option<Self> {
+        self.combine(other, |a, b| a | b)
+    }
+
+    pub fn intersection(self, other: AnswerSet) -> Option<Self> {
+        self.combine(other, |a, b| a & b)
+    }
+
+    pub fn difference(self, other: AnswerSet) -> Option<Self> {
+        self.combine(other, |a, b| a & !b)
+    }
+
+    /// The questions in the set, in alphabet order
+    pub fn iter(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.alphabet
+            .questions()
+            .enumerate()
+            .filter(move |(idx, _)| self.contains_index(*idx))
+            .map(|(_, q)| q)
+    }
+}
+impl<'a> PartialEq for AnswerSet<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bits == other.bits && self.same_alphabet(other)
+    }
+}
+impl<'a> Eq for AnswerSet<'a> {}
+impl<'a> Hash for AnswerSet<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.bits.hash(state);
+    }
+}
+impl<'a> fmt::Display for AnswerSet<'a> {
+    /// The questions in the set, in order, e.g. `abx` for single-character
+    /// questions or `q1 q2` for longer ones
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let separator = if self.alphabet.is_chars() { "" } else { " " };
+        self.iter().enumerate().try_for_each(|(idx, q)| match idx {
+            0 => f.write_str(q),
+            _ => write!(f, "{}{}", separator, q),
+        })
+    }
+}
+impl<'a> fmt::Debug for AnswerSet<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn set_operations() {
+        let alphabet = Alphabet::lowercase();
+        let abc = AnswerSet::from_questions(alphabet, vec!["c", "a", "b"]);
+        let bcd = AnswerSet::from_questions(alphabet, vec!["b", "c", "d", "!"]);
+        assert_eq!(abc.union(bcd).unwrap().to_string(), "abcd");
+        assert_eq!(abc.intersection(bcd).unwrap().to_string(), "bc");
+        assert_eq!(abc.difference(bcd).unwrap().to_string(), "a");
+        assert_eq!(abc.len(), 3);
+        assert!(abc.contains("a") && !abc.contains("d") && !abc.contains("!"));
+        assert_eq!(AnswerSet::all(alphabet).len(), 26);
+        assert!(AnswerSet::empty(alphabet).is_empty());
+        assert_eq!(abc.iter().collect::<Vec<_>>(), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn sets_from_different_alphabets_do_not_mix() {
+        let lowercase = AnswerSet::all(Alphabet::lowercase());
+        // an equal alphabet built separately is still the same alphabet
+        let same = Alphabet::from_chars('a'..='z').unwrap();
+        assert_eq!(lowercase, AnswerSet::all(&same));
+        assert_eq!(lowercase.union(AnswerSet::all(&same)).unwrap().len(), 26);
+        let other = Alphabet::from_chars('a'..='c').unwrap();
+        assert_ne!(
+            AnswerSet::all(&other),
+            AnswerSet::from_questions(&same, vec!["a", "b", "c"])
+        );
+        assert!(lowercase.union(AnswerSet::all(&other)).is_none());
+        assert!(lowercase.intersection(AnswerSet::empty(&other)).is_none());
+        assert!(lowercase.difference(AnswerSet::empty(&other)).is_none());
+    }
+}
@@ -0,0 +1,183 @@
+use std::fmt;
+
+/// Where in the input something went wrong, and what was there
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    /// 1-indexed
+    pub line: usize,
+    /// 1-indexed, counted in characters rather than bytes
+    pub column: usize,
+    /// The offending text, which is empty if something was missing
+    pub text: String,
+    /// The whole line the text is on, for rendering snippets
+    pub source_line: String,
+}
+impl Location {
+    /// The location of the `len` bytes starting at byte `offset` of `input`
+    pub fn at(input: &str, offset: usize, len: usize) -> Self {
+        let line_start = input[..offset].rfind('\n').map_or(0, |idx| idx + 1);
+        let line_end = input[offset..]
+            .find('\n')
+            .map_or(input.len(), |idx| offset + idx);
+        Self {
+            line: input[..offset].matches('\n').count() + 1,
+            column: input[line_start..offset].chars().count() + 1,
+            text: input[offset..offset + len].to_owned(),
+            source_line: input[line_start..line_end]
+                .trim_end_matches('\r')
+                .to_owned(),
+        }
+    }
+
+    /// The location of `text` within `input`
+    ///
+    /// Panics if `text` isn't a slice of `input`.
+    pub fn of(input: &str, text: &str) -> Self {
+        let offset = (text.as_ptr() as usize)
+            .checked_sub(input.as_ptr() as usize)
+            .filter(|offset| offset + text.len() <= input.len())
+            .expect("text must be a slice of the input");
+        Self::at(input, offset, text.len())
+    }
+
+    /// The (empty) location just after the end of `input`, for things that
+    /// should have been there but weren't
+    pub fn end_of(input: &str) -> Self {
+        Self::at(input, input.len(), 0)
+    }
+}
+
+/// Something wrong with a puzzle input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// A character that doesn't belong here
+    InvalidChar { at: Location, expected: String },
+    /// Text that should have been a number
+    InvalidNumber { at: Location },
+    /// Something that should have been there but wasn't
+    Missing { at: Location, what: String },
+    /// Text that isn't shaped like what it should be
+    Malformed {
+        at: Location,
+        what: String,
+        expected: String,
+    },
+    /// Text that's shaped right but isn't an allowed value
+    Invalid { at: Location, what: String },
+}
+impl ParseError {
+    pub fn location(&self) -> &Location {
+        match self {
+            ParseError::InvalidChar { at, .. }
+            | ParseError::InvalidNumber { at }
+            | ParseError::Missing { at, .. }
+            | ParseError::Malformed { at, .. }
+            | ParseError::Invalid { at, .. } => at,
+        }
+    }
+
+    /// Move the error down by the given number of lines, for when only part
+    /// of the input was parsed, e.g. one line or one record at a time
+    pub fn offset_lines(mut self, lines: usize) -> Self {
+        match &mut self {
+            ParseError::InvalidChar { at, .. }
+            | ParseError::InvalidNumber { at }
+            | ParseError::Missing { at, .. }
+            | ParseError::Malformed { at, .. }
+            | ParseError::Invalid { at, .. } => at.line += lines,
+        }
+        self
+    }
+
+    /// The source line with the offending text underlined, like:
+    ///
+    /// ```text
+    ///   |
+    /// 4 | 1-3 a abcde
+    ///   |       ^
+    /// ```
+    pub fn snippet(&self) -> String {
+        let at = self.location();
+        let number = at.line.to_string();
+        let gutter = " ".repeat(number.len());
+        format!(
+            "{} |\n{} | {}\n{} | {}{}",
+            gutter,
+            number,
+            at.source_line,
+            gutter,
+            " ".repeat(at.column - 1),
+            "^".repeat(at.text.chars().count().max(1)),
+        )
+    }
+}
+impl fmt::Display for ParseError {
+    /// A one-line description, or with `{:#}`, a description followed by a
+    /// snippet of the input
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let at = self.location();
+        match self {
+            ParseError::InvalidChar { expected, .. } => write!(
+                f,
+                "Invalid character {:?} at line {}, column {}, expected {}",
+                at.text, at.line, at.column, expected
+            ),
+            ParseError::InvalidNumber { .. } => write!(
+                f,
+                "Invalid number {:?} at line {}, column {}",
+                at.text, at.line, at.column
+            ),
+            ParseError::Missing { what, .. } => write!(
+                f,
+                "Missing {} at line {}, column {}",
+                what, at.line, at.column
+            ),
+            ParseError::Malformed { what, expected, .. } => write!(
+                f,
+                "Malformed {} {:?} at line {}, column {}, expected {}",
+                what, at.text, at.line, at.column, expected
+            ),
+            ParseError::Invalid { what, .. } => write!(
+                f,
+                "Invalid {} {:?} at line {}, column {}",
+                what, at.text, at.line, at.column
+            ),
+        }?;
+        match f.alternate() {
+            true => write!(f, "\n{}", self.snippet()),
+            false => Ok(()),
+        }
+    }
+}
+impl std::error::Error for ParseError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn locations() {
+        let input = "abc\r\ndéf ghi\n";
+        let at = Location::of(input, &input[10..13]);
+        assert_eq!((at.line, at.column), (2, 5));
+        assert_eq!(
+            (at.text.as_str(), at.source_line.as_str()),
+            ("ghi", "déf ghi")
+        );
+        let end = Location::end_of(&input[..3]);
+        assert_eq!((end.line, end.column, end.text.as_str()), (1, 4, ""));
+    }
+
+    #[test]
+    fn errors_render_snippets() {
+        let input = "1-3 a: abcde\n1-3 b: cdefg\n2-x c: ccccc\n";
+        let err = ParseError::InvalidNumber {
+            at: Location::of(input, &input[28..29]),
+        };
+        assert_eq!(err.to_string(), "Invalid number \"x\" at line 3, column 3");
+        assert_eq!(
+            format!("{:#}", err.offset_lines(7)),
+            "Invalid number \"x\" at line 10, column 3\n   |\n10 | 2-x c: ccccc\n   |   ^"
+        );
+    }
+}
@@ -0,0 +1,128 @@
+//! Random puzzle inputs, for stress testing and benchmarking
+//!
+//! Everything is generated from a seed, so the same seed always gives the
+//! same input.
+
+use anyhow::{anyhow, Error};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{day1, day2, day3, day4, day5, day6};
+
+/// How wide the generated day 3 forests are, like the puzzle's
+const FOREST_WIDTH: usize = 31;
+
+/// The size of the day's real puzzle input, see `generate`
+pub fn puzzle_size(day: u8) -> Option<usize> {
+    match day {
+        1 => Some(200),
+        2 => Some(1000),
+        3 => Some(323),
+        4 => Some(290),
+        5 => Some(761),
+        6 => Some(490),
+        _ => None,
+    }
+}
+
+/// Generate an input for `day`, reproducibly from `seed`
+///
+/// `size` is how many entries, passwords, rows of forest, passports,
+/// boarding passes or groups there are, depending on the day. Day 5's
+/// passes are all for the puzzle's plane, since that's the only one its
+/// solutions know, so there can be at most 1023 of them. With `invalid`, a
+/// line that doesn't belong is slipped in somewhere, for checking that it
+/// gets reported.
+pub fn generate(
+    day: u8,
+    size: usize,
+    seed: u64,
+    invalid: bool,
+) -> Result<String, Error> {
+    let rng = &mut StdRng::seed_from_u64(seed);
+    let (input, bad_line) = match day {
+        1 => (day1::generate_expenses(size, rng)?, "12x4"),
+        2 => (day2::generate_passwords(size, rng), "1-3 a abcde"),
+        3 => (
+            day3::generate_forest(FOREST_WIDTH, size, 0.25, rng)?,
+            "..X..",
+        ),
+        4 => (day4::generate_passports(size, rng), "ecl:brn pid"),
+        5 => {
+            let layout = day5::AircraftLayout::STANDARD;
+            let missing = rng.gen_range(1..layout.seats() - 1);
+            (
+                day5::generate_passes(&layout, size, missing, rng)?,
+                "FBFBXFFRLR",
+            )
+        }
+        6 => (day6::generate_customs_forms(size, rng), "ab1"),
+        _ => return Err(anyhow!("There's no generator for day {}", day)),
+    };
+    Ok(match invalid {
+        true => insert_line(&input, bad_line, rng),
+        false => input,
+    })
+}
+
+/// Put `line` in `input` before a random one of its lines, or at the end
+fn insert_line<R: Rng>(input: &str, line: &str, rng: &mut R) -> String {
+    let mut lines = input.lines().collect::<Vec<_>>();
+    lines.insert(rng.gen_range(0..=lines.len()), line);
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::day1::{Pairs, Triads};
+    use crate::day6::{alphabet::Alphabet, CustomsForm};
+    use crate::input::{integers, key_values, records, ParseMode};
+    use crate::runner::solutions;
+
+    #[test]
+    fn expenses_have_one_pair_and_one_triad() {
+        let entries = integers::<usize>(&generate(1, 60, 7, false).unwrap()).unwrap();
+        assert_eq!(entries.len(), 60);
+        let pairs = entries.clone().pairs().filter(|(i, j)| i + j == 2020);
+        assert_eq!(pairs.count(), 1);
+        let triads = entries.triads().filter(|(i, j, k)| i + j + k == 2020);
+        assert_eq!(triads.count(), 1);
+        assert!(generate(1, 4, 7, false).is_err());
+    }
+
+    #[test]
+    fn every_day_generates_inputs() {
+        for solution in solutions().iter().filter(|s| s.day <= 6) {
+            let size = puzzle_size(solution.day).unwrap();
+            let input = generate(solution.day, size, 3, false).unwrap();
+            // the same seed gives the same input
+            assert_eq!(generate(solution.day, size, 3, false).unwrap(), input);
+            assert_ne!(generate(solution.day, size, 4, false).unwrap(), input);
+            assert!((solution.solve)(&input).is_ok());
+            let bad = generate(solution.day, size, 3, true).unwrap();
+            assert_eq!(bad.lines().count(), input.lines().count() + 1);
+            match solution.day {
+                // these solve leniently, so the bad line has to be looked for
+                4 => assert!(key_values(&bad).any(|field| field.is_err())),
+                6 => assert!(records(&bad).any(|record| {
+                    CustomsForm::parse(
+                        &record,
+                        Alphabet::lowercase(),
+                        ParseMode::Strict,
+                    )
+                    .is_err()
+                })),
+                _ => assert!((solution.solve)(&bad).is_err()),
+            }
+        }
+        assert!(key_values(&generate(4, 40, 3, false).unwrap()).all(|f| f.is_ok()));
+        // day 5 scales the number of passes, not the plane
+        let passes = generate(5, 40, 3, false).unwrap();
+        assert_eq!(passes.lines().count(), 40);
+        assert!(solutions()
+            .iter()
+            .all(|s| s.day != 5 || (s.solve)(&passes).is_ok()));
+        assert!(generate(5, 1024, 3, false).is_err());
+        assert!(generate(7, 40, 3, false).is_err());
+    }
+}
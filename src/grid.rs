@@ -0,0 +1,567 @@
+//! A rectangular grid of cells, for puzzles that take place on a map
+//!
+//! Coordinates start at 0,0 (x, y) in the top left, with x going right and y
+//! going down, so a 2-by-2 grid has a max coordinate of 1,1.
+
+use anyhow::{anyhow, Error};
+use std::ops::{Index, IndexMut};
+
+use crate::error::ParseError;
+use crate::input::char_grid;
+
+/// A point on a grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Coordinate {
+    pub x: usize,
+    pub y: usize,
+}
+impl Coordinate {
+    pub fn new(x: usize, y: usize) -> Self {
+        Coordinate { x, y }
+    }
+
+    /// The coordinate one `vector` away, if it isn't off the top or left
+    pub fn step(self, vector: Vector) -> Option<Self> {
+        Some(Coordinate::new(
+            offset(self.x, vector.delta_x)?,
+            offset(self.y, vector.delta_y)?,
+        ))
+    }
+}
+
+fn offset(value: usize, delta: isize) -> Option<usize> {
+    match delta < 0 {
+        true => value.checked_sub(delta.unsigned_abs()),
+        false => value.checked_add(delta as usize),
+    }
+}
+
+/// A direction of travel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Vector {
+    pub delta_x: isize,
+    pub delta_y: isize,
+}
+impl Vector {
+    pub const UP: Vector = Vector::new(0, -1);
+    pub const DOWN: Vector = Vector::new(0, 1);
+    pub const LEFT: Vector = Vector::new(-1, 0);
+    pub const RIGHT: Vector = Vector::new(1, 0);
+    pub const UP_LEFT: Vector = Vector::new(-1, -1);
+    pub const UP_RIGHT: Vector = Vector::new(1, -1);
+    pub const DOWN_LEFT: Vector = Vector::new(-1, 1);
+    pub const DOWN_RIGHT: Vector = Vector::new(1, 1);
+
+    /// The directions to the 4-connected neighbours, clockwise from up
+    pub const ORTHOGONAL: [Vector; 4] = [Self::UP, Self::RIGHT, Self::DOWN, Self::LEFT];
+
+    /// The directions to the 8-connected neighbours, clockwise from up
+    pub const ALL: [Vector; 8] = [
+        Self::UP,
+        Self::UP_RIGHT,
+        Self::RIGHT,
+        Self::DOWN_RIGHT,
+        Self::DOWN,
+        Self::DOWN_LEFT,
+        Self::LEFT,
+        Self::UP_LEFT,
+    ];
+
+    pub const fn new(delta_x: isize, delta_y: isize) -> Self {
+        Vector { delta_x, delta_y }
+    }
+}
+
+/// Which edges of a grid carry on around to the opposite edge
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wrap {
+    None,
+    /// Off the right edge is back onto the left, and vice versa
+    Horizontal,
+    /// Off the bottom edge is back onto the top, and vice versa
+    Vertical,
+    Both,
+}
+impl Wrap {
+    fn horizontal(self) -> bool {
+        matches!(self, Wrap::Horizontal | Wrap::Both)
+    }
+
+    fn vertical(self) -> bool {
+        matches!(self, Wrap::Vertical | Wrap::Both)
+    }
+}
+
+/// A rectangular grid of cells
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    // row by row, from the top
+    cells: Vec<T>,
+}
+impl<T> Grid<T> {
+    /// A grid from its rows, top first, which all have to be the same width
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Self, Error> {
+        let width = rows.first().map_or(0, Vec::len);
+        if let Some((idx, row)) =
+            rows.iter().enumerate().find(|(_, r)| r.len() != width)
+        {
+            return Err(anyhow!(
+                "Row {} has {} cells, expected {}",
+                idx,
+                row.len(),
+                width
+            ));
+        }
+        Ok(Self {
+            width,
+            height: rows.len(),
+            cells: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    /// A grid with each cell filled in by `cell`, row by row from the top
+    pub fn from_fn<F>(width: usize, height: usize, mut cell: F) -> Self
+    where
+        F: FnMut(Coordinate) -> T,
+    {
+        Self {
+            width,
+            height,
+            cells: (0..height)
+                .flat_map(|y| (0..width).map(move |x| Coordinate::new(x, y)))
+                .map(&mut cell)
+                .collect(),
+        }
+    }
+
+    /// Parse a map of characters, one row per line, with `cell` turning each
+    /// character into a cell. See `input::char_grid` for the details.
+    pub fn parse<F>(input: &str, cell: F, expected: &str) -> Result<Self, ParseError>
+    where
+        F: Fn(char) -> Option<T>,
+    {
+        let rows = char_grid(input, cell, expected)?;
+        Ok(
+            Self::from_rows(rows)
+                .expect("char_grid checks the rows are the same width"),
+        )
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, coord: Coordinate) -> bool {
+        coord.x < self.width && coord.y < self.height
+    }
+
+    pub fn get(&self, coord: Coordinate) -> Option<&T> {
+        match self.contains(coord) {
+            true => Some(&self.cells[coord.y * self.width + coord.x]),
+            false => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, coord: Coordinate) -> Option<&mut T> {
+        match self.contains(coord) {
+            true => Some(&mut self.cells[coord.y * self.width + coord.x]),
+            false => None,
+        }
+    }
+
+    /// Where `x`, `y` ends up on the grid once the edges in `wrap` have
+    /// wrapped around, or `None` if it's off an edge that doesn't wrap
+    pub fn wrapped(&self, x: isize, y: isize, wrap: Wrap) -> Option<Coordinate> {
+        let axis = |value: isize, size: usize, wraps: bool| match wraps {
+            _ if size == 0 => None,
+            true => Some(value.rem_euclid(size as isize) as usize),
+            false if value >= 0 && (value as usize) < size => Some(value as usize),
+            false => None,
+        };
+        Some(Coordinate::new(
+            axis(x, self.width, wrap.horizontal())?,
+            axis(y, self.height, wrap.vertical())?,
+        ))
+    }
+
+    /// Every coordinate on the grid, row by row from the top
+    pub fn coordinates(&self) -> impl Iterator<Item = Coordinate> {
+        let width = self.width;
+        (0..self.height)
+            .flat_map(move |y| (0..width).map(move |x| Coordinate::new(x, y)))
+    }
+
+    /// Every cell along with its coordinate, row by row from the top
+    pub fn iter(&self) -> impl Iterator<Item = (Coordinate, &T)> {
+        self.coordinates().zip(self.cells.iter())
+    }
+
+    pub fn row(&self, y: usize) -> Option<&[T]> {
+        match y < self.height {
+            true => Some(&self.cells[y * self.width..(y + 1) * self.width]),
+            false => None,
+        }
+    }
+
+    /// The rows, from the top
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).filter_map(move |y| self.row(y))
+    }
+
+    /// The cells in a column, from the top
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        let cells = match x < self.width {
+            true => &self.cells[x..],
+            false => &[],
+        };
+        cells.iter().step_by(self.width.max(1))
+    }
+
+    /// The columns, from the left
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |x| self.column(x))
+    }
+
+    /// The diagonals that run down and to the right, starting with the one
+    /// in the bottom left corner and ending in the top right corner
+    pub fn diagonals(&self) -> impl Iterator<Item = Ray<'_, T>> {
+        let starts = (0..self.height)
+            .rev()
+            .map(|y| Coordinate::new(0, y))
+            .chain((1..self.width).map(|x| Coordinate::new(x, 0)));
+        starts.map(move |start| self.ray(start, Vector::DOWN_RIGHT, Wrap::None))
+    }
+
+    /// The diagonals that run down and to the left, starting with the one
+    /// in the top left corner and ending in the bottom right corner
+    pub fn anti_diagonals(&self) -> impl Iterator<Item = Ray<'_, T>> {
+        let last_x = self.width.saturating_sub(1);
+        let starts = (0..self.width)
+            .map(|x| Coordinate::new(x, 0))
+            .chain((1..self.height).map(move |y| Coordinate::new(last_x, y)));
+        starts.map(move |start| self.ray(start, Vector::DOWN_LEFT, Wrap::None))
+    }
+
+    /// The cells one step away in each of `directions`, that are on the grid
+    pub fn neighbours<'a>(
+        &'a self,
+        coord: Coordinate,
+        directions: &'a [Vector],
+    ) -> impl Iterator<Item = (Coordinate, &'a T)> + 'a {
+        directions
+            .iter()
+            .filter_map(move |v| coord.step(*v))
+            .filter_map(move |c| self.get(c).map(|cell| (c, cell)))
+    }
+
+    /// The cells above, below, left and right of `coord`
+    pub fn neighbours4(
+        &self,
+        coord: Coordinate,
+    ) -> impl Iterator<Item = (Coordinate, &T)> {
+        self.neighbours(coord, &Vector::ORTHOGONAL)
+    }
+
+    /// The cells surrounding `coord`, including diagonally
+    pub fn neighbours8(
+        &self,
+        coord: Coordinate,
+    ) -> impl Iterator<Item = (Coordinate, &T)> {
+        self.neighbours(coord, &Vector::ALL)
+    }
+
+    /// The cells from `start` onwards, one `vector` at a time, until the ray
+    /// goes off an edge that doesn't wrap
+    ///
+    /// If the ray can't leave the grid, e.g. with `Wrap::Both` or a zero
+    /// vector, it never ends.
+    pub fn ray(&self, start: Coordinate, vector: Vector, wrap: Wrap) -> Ray<'_, T> {
+        Ray {
+            grid: self,
+            position: Some((start.x as isize, start.y as isize)),
+            vector,
+            wrap,
+        }
+    }
+
+    /// The first cell in the direction of `vector` from `start`, not counting
+    /// `start` itself, that matches `predicate`, without wrapping
+    pub fn cast<P>(
+        &self,
+        start: Coordinate,
+        vector: Vector,
+        mut predicate: P,
+    ) -> Option<(Coordinate, &T)>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        self.ray(start, vector, Wrap::None)
+            .skip(1)
+            .take_while(|(c, _)| *c != start)
+            .find(|(_, cell)| predicate(cell))
+    }
+
+    /// A `width` by `height` window onto the grid with its top left corner
+    /// at `origin`, or `None` if it doesn't fit
+    pub fn view(
+        &self,
+        origin: Coordinate,
+        width: usize,
+        height: usize,
+    ) -> Option<GridView<'_, T>> {
+        match origin.x + width <= self.width && origin.y + height <= self.height {
+            true => Some(GridView {
+                grid: self,
+                origin,
+                width,
+                height,
+            }),
+            false => None,
+        }
+    }
+
+    /// A grid of the same shape with `f` applied to each cell
+    pub fn map<U, F>(&self, f: F) -> Grid<U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    /// Draw the grid back out as characters, one row per line
+    pub fn render<F>(&self, cell: F) -> String
+    where
+        F: Fn(&T) -> char,
+    {
+        self.rows()
+            .map(|row| row.iter().map(&cell).chain(Some('\n')).collect::<String>())
+            .collect()
+    }
+}
+impl<T: Clone> Grid<T> {
+    fn rearranged<F>(&self, width: usize, height: usize, source: F) -> Self
+    where
+        F: Fn(Coordinate) -> Coordinate,
+    {
+        Self::from_fn(width, height, |c| self[source(c)].clone())
+    }
+
+    /// Swap the rows and the columns
+    pub fn transpose(&self) -> Self {
+        self.rearranged(self.height, self.width, |c| Coordinate::new(c.y, c.x))
+    }
+
+    /// Turn the grid a quarter turn to the right
+    pub fn rotate_clockwise(&self) -> Self {
+        let last_y = self.height.saturating_sub(1);
+        self.rearranged(self.height, self.width, |c| {
+            Coordinate::new(c.y, last_y - c.x)
+        })
+    }
+
+    /// Turn the grid a quarter turn to the left
+    pub fn rotate_counterclockwise(&self) -> Self {
+        let last_x = self.width.saturating_sub(1);
+        self.rearranged(self.height, self.width, |c| {
+            Coordinate::new(last_x - c.y, c.x)
+        })
+    }
+
+    /// Mirror the grid left to right
+    pub fn flip_horizontal(&self) -> Self {
+        let last_x = self.width.saturating_sub(1);
+        self.rearranged(self.width, self.height, |c| {
+            Coordinate::new(last_x - c.x, c.y)
+        })
+    }
+
+    /// Mirror the grid top to bottom
+    pub fn flip_vertical(&self) -> Self {
+        let last_y = self.height.saturating_sub(1);
+        self.rearranged(self.width, self.height, |c| {
+            Coordinate::new(c.x, last_y - c.y)
+        })
+    }
+}
+impl<T> Index<Coordinate> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, coord: Coordinate) -> &T {
+        self.get(coord).expect("coordinate is off the grid")
+    }
+}
+impl<T> IndexMut<Coordinate> for Grid<T> {
+    fn index_mut(&mut self, coord: Coordinate) -> &mut T {
+        self.get_mut(coord).expect("coordinate is off the grid")
+    }
+}
+
+/// The cells along a straight line across a grid
+pub struct Ray<'a, T> {
+    grid: &'a Grid<T>,
+    // where the next cell is, before wrapping, or None once the ray is done
+    position: Option<(isize, isize)>,
+    vector: Vector,
+    wrap: Wrap,
+}
+impl<'a, T> Iterator for Ray<'a, T> {
+    type Item = (Coordinate, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (x, y) = self.position?;
+        let coord = self.grid.wrapped(x, y, self.wrap);
+        self.position = coord.map(|c| {
+            (
+                c.x as isize + self.vector.delta_x,
+                c.y as isize + self.vector.delta_y,
+            )
+        });
+        coord.map(|c| (c, &self.grid[c]))
+    }
+}
+
+/// A rectangular window onto part of a grid, with its own coordinates
+/// starting at 0,0 in its top left
+#[derive(Debug, Clone, Copy)]
+pub struct GridView<'a, T> {
+    grid: &'a Grid<T>,
+    origin: Coordinate,
+    width: usize,
+    height: usize,
+}
+impl<'a, T> GridView<'a, T> {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Where the view's top left corner is on the grid
+    pub fn origin(&self) -> Coordinate {
+        self.origin
+    }
+
+    pub fn get(&self, coord: Coordinate) -> Option<&'a T> {
+        match coord.x < self.width && coord.y < self.height {
+            true => self.grid.get(Coordinate::new(
+                self.origin.x + coord.x,
+                self.origin.y + coord.y,
+            )),
+            false => None,
+        }
+    }
+
+    /// The rows of the view, from the top
+    pub fn rows(&self) -> impl Iterator<Item = &'a [T]> {
+        let Self {
+            grid,
+            origin,
+            width,
+            height,
+        } = *self;
+        (origin.y..origin.y + height)
+            .filter_map(move |y| grid.row(y))
+            .map(move |row| &row[origin.x..origin.x + width])
+    }
+
+    /// Copy the view out into a grid of its own
+    pub fn to_grid(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid::from_fn(self.width, self.height, |c| {
+            self.get(c).expect("the coordinate is in the view").clone()
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn grid() -> Grid<char> {
+        Grid::parse("abc\ndef\n", Some, "anything").unwrap()
+    }
+
+    fn text(cells: impl IntoIterator<Item = impl std::borrow::Borrow<char>>) -> String {
+        cells.into_iter().map(|c| *c.borrow()).collect()
+    }
+
+    #[test]
+    fn cells_rows_and_columns() {
+        let grid = grid();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[Coordinate::new(2, 1)], 'f');
+        assert_eq!(grid.get(Coordinate::new(3, 0)), None);
+        assert_eq!(
+            grid.wrapped(-1, 1, Wrap::Horizontal),
+            Some(Coordinate::new(2, 1))
+        );
+        assert_eq!(grid.wrapped(-1, 1, Wrap::Vertical), None);
+        assert_eq!(
+            grid.rows().map(text).collect::<Vec<_>>(),
+            vec!["abc", "def"]
+        );
+        assert_eq!(text(grid.column(1)), "be");
+        assert_eq!(
+            grid.columns().map(text).collect::<Vec<_>>(),
+            vec!["ad", "be", "cf"]
+        );
+        let diagonals = grid.diagonals().map(|r| text(r.map(|(_, c)| c)));
+        assert_eq!(diagonals.collect::<Vec<_>>(), vec!["d", "ae", "bf", "c"]);
+        let anti = grid.anti_diagonals().map(|r| text(r.map(|(_, c)| c)));
+        assert_eq!(anti.collect::<Vec<_>>(), vec!["a", "bd", "ce", "f"]);
+        assert!(Grid::from_rows(vec![vec![1, 2], vec![3]]).is_err());
+        assert_eq!(grid.render(|c| *c), "abc\ndef\n");
+    }
+
+    #[test]
+    fn neighbours_and_rays() {
+        let grid = grid();
+        let corner = Coordinate::new(0, 0);
+        let cells =
+            |n: &mut dyn Iterator<Item = (Coordinate, &char)>| text(n.map(|(_, c)| c));
+        assert_eq!(cells(&mut grid.neighbours4(corner)), "bd");
+        assert_eq!(cells(&mut grid.neighbours8(Coordinate::new(1, 1))), "bcfda");
+        let mut ray =
+            grid.ray(Coordinate::new(1, 0), Vector::new(2, 1), Wrap::Horizontal);
+        assert_eq!(cells(&mut ray), "bd");
+        let mut looped = grid.ray(corner, Vector::RIGHT, Wrap::Both).take(5);
+        assert_eq!(cells(&mut looped), "abcab");
+        let seen = grid.cast(corner, Vector::RIGHT, |c| *c != 'b');
+        assert_eq!(seen, Some((Coordinate::new(2, 0), &'c')));
+        assert_eq!(grid.cast(corner, Vector::UP, |_| true), None);
+    }
+
+    #[test]
+    fn transforms_and_views() {
+        let grid = grid();
+        let render = |g: Grid<char>| g.render(|c| *c);
+        assert_eq!(render(grid.transpose()), "ad\nbe\ncf\n");
+        assert_eq!(render(grid.rotate_clockwise()), "da\neb\nfc\n");
+        assert_eq!(render(grid.rotate_counterclockwise()), "cf\nbe\nad\n");
+        assert_eq!(render(grid.flip_horizontal()), "cba\nfed\n");
+        assert_eq!(render(grid.flip_vertical()), "def\nabc\n");
+        let turned = (0..4).fold(grid.clone(), |g, _| g.rotate_clockwise());
+        assert_eq!(turned, grid);
+
+        let view = grid.view(Coordinate::new(1, 0), 2, 2).unwrap();
+        assert_eq!(view.get(Coordinate::new(1, 1)), Some(&'f'));
+        assert_eq!(view.get(Coordinate::new(2, 0)), None);
+        assert_eq!(view.rows().map(text).collect::<Vec<_>>(), vec!["bc", "ef"]);
+        assert_eq!(render(view.to_grid()), "bc\nef\n");
+        assert!(grid.view(Coordinate::new(2, 0), 2, 1).is_none());
+    }
+}
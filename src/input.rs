@@ -0,0 +1,275 @@
+//! Helpers for reading puzzle input
+//!
+//! Everything here reports problems as a `ParseError` pointing at where
+//! they are in the whole input.
+
+use std::{ops::Range, str::FromStr};
+
+use crate::error::{Location, ParseError};
+
+/// How to treat questionable input
+///
+/// Each puzzle decides what counts as questionable and what leniency means
+/// for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Report it as an error
+    Strict,
+    /// Accept what can be made sense of, and skip or flag the rest
+    Lenient,
+}
+
+/// One line of some input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Line<'a> {
+    /// The text of the line, without its line ending
+    pub text: &'a str,
+    /// 1-indexed
+    pub number: usize,
+}
+
+/// Iterate over the lines in the input, along with their line numbers
+pub fn lines(input: &str) -> impl Iterator<Item = Line<'_>> {
+    input.lines().enumerate().map(|(idx, text)| Line {
+        text,
+        number: idx + 1,
+    })
+}
+
+/// Parse each non-blank line of the input
+///
+/// `parse` only sees one line, so the errors it returns are on line 1. They
+/// get moved to the line they actually came from.
+pub fn parse_lines<'a, T, F>(input: &'a str, mut parse: F) -> Result<Vec<T>, ParseError>
+where
+    F: FnMut(&'a str) -> Result<T, ParseError>,
+{
+    lines(input)
+        .filter(|line| !line.text.trim().is_empty())
+        .map(|line| parse(line.text).map_err(|e| e.offset_lines(line.number - 1)))
+        .collect()
+}
+
+/// A group of consecutive non-blank lines from some input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record<'a> {
+    /// The text of the record, from the start of its first line to the end
+    /// of its last line, not including the final line ending
+    pub text: &'a str,
+    /// The byte offsets of the text in the input
+    pub span: Range<usize>,
+    /// The 1-indexed line numbers of the record, from its first line up to
+    /// but not including the line after its last
+    pub lines: Range<usize>,
+}
+impl<'a> Record<'a> {
+    /// Move an error from parsing the record's text on its own to where the
+    /// record is in the whole input
+    pub fn relocate(&self, error: ParseError) -> ParseError {
+        error.offset_lines(self.lines.start - 1)
+    }
+}
+
+/// An iterator over the blank-line-separated records in some input
+pub struct Records<'a> {
+    input: &'a str,
+    offset: usize,
+    line: usize,
+}
+impl<'a> Iterator for Records<'a> {
+    type Item = Record<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // (byte offset, line number) of the start and end of the record
+        let mut start: Option<(usize, usize)> = None;
+        let mut end = (self.offset, self.line);
+        // Walk line by line, where a line includes its line ending if it has
+        // one. Blank lines, including those that are only whitespace or a
+        // "\r" from a CRLF ending, separate records.
+        for line in self.input[self.offset..].split_inclusive('\n') {
+            let line_start = self.offset;
+            self.offset += line.len();
+            self.line += 1;
+            if line.trim().is_empty() {
+                if start.is_some() {
+                    break;
+                }
+                continue;
+            }
+            start.get_or_insert((line_start, self.line));
+            end = (
+                line_start + line.trim_end_matches(&['\r', '\n'][..]).len(),
+                self.line,
+            );
+        }
+        start.map(|(start, first_line)| Record {
+            text: &self.input[start..end.0],
+            span: start..end.0,
+            lines: first_line..end.1 + 1,
+        })
+    }
+}
+
+/// Iterate over the blank-line-separated records in the input
+///
+/// Unlike `input.split("\n\n")` this copes with CRLF line endings, any
+/// number of blank lines between records, lines that contain only
+/// whitespace, and leading or trailing blank lines.
+pub fn records(input: &str) -> Records<'_> {
+    Records {
+        input,
+        offset: 0,
+        line: 0,
+    }
+}
+
+/// A `key:value` pair
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Field<'a> {
+    pub key: &'a str,
+    /// Everything after the first `:`, which may contain more of them
+    pub value: &'a str,
+    /// The whole `key:value` text
+    pub token: &'a str,
+}
+
+/// Iterate over the whitespace-separated `key:value` pairs in some text
+pub fn key_values(text: &str) -> impl Iterator<Item = Result<Field<'_>, ParseError>> {
+    text.split_whitespace().map(move |token| {
+        token
+            .find(':')
+            .map(|idx| Field {
+                key: &token[..idx],
+                value: &token[idx + 1..],
+                token,
+            })
+            .ok_or_else(|| ParseError::Malformed {
+                at: Location::of(text, token),
+                what: "field".into(),
+                expected: "key:value".into(),
+            })
+    })
+}
+
+/// Read a list of integers, separated by any mix of whitespace and commas
+pub fn integers<T: FromStr>(input: &str) -> Result<Vec<T>, ParseError> {
+    input
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|token| !token.is_empty())
+        .map(|token| {
+            token.parse().map_err(|_| ParseError::InvalidNumber {
+                at: Location::of(input, token),
+            })
+        })
+        .collect()
+}
+
+/// Read a rectangular grid of characters, one row per non-blank line, with
+/// `cell` turning each character into whatever the grid holds
+///
+/// Leading and trailing whitespace on each line is ignored. Characters that
+/// `cell` rejects are reported as not being `expected`, e.g. "'.' or '#'".
+pub fn char_grid<T, F>(
+    input: &str,
+    cell: F,
+    expected: &str,
+) -> Result<Vec<Vec<T>>, ParseError>
+where
+    F: Fn(char) -> Option<T>,
+{
+    let rows = input
+        .lines()
+        .map(str::trim)
+        .filter(|ln| !ln.is_empty())
+        .map(|ln| {
+            ln.char_indices()
+                .map(|(idx, c)| {
+                    cell(c).ok_or_else(|| ParseError::InvalidChar {
+                        at: Location::of(input, &ln[idx..idx + c.len_utf8()]),
+                        expected: expected.into(),
+                    })
+                })
+                .collect::<Result<Vec<T>, ParseError>>()
+                .map(|row| (ln, row))
+        })
+        .collect::<Result<Vec<_>, ParseError>>()?;
+    let width = match rows.first() {
+        Some((_, row)) => row.len(),
+        None => {
+            return Err(ParseError::Missing {
+                at: Location::end_of(input),
+                what: "grid".into(),
+            })
+        }
+    };
+    if let Some((ln, _)) = rows.iter().find(|(_, row)| row.len() != width) {
+        return Err(ParseError::Malformed {
+            at: Location::of(input, ln),
+            what: "row".into(),
+            expected: format!("{} columns", width),
+        });
+    }
+    Ok(rows.into_iter().map(|(_, row)| row).collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn records_are_split_on_blank_lines() {
+        let input = "\na b\nc\n\n\n\nd\r\n  \t\r\ne f\r\n\r\n  \n";
+        let records = records(input).collect::<Vec<_>>();
+        assert_eq!(
+            records.iter().map(|r| r.text).collect::<Vec<_>>(),
+            vec!["a b\nc", "d", "e f"]
+        );
+        assert_eq!(
+            records.iter().map(|r| r.lines.clone()).collect::<Vec<_>>(),
+            vec![2..4, 7..8, 9..10]
+        );
+        records
+            .iter()
+            .for_each(|r| assert_eq!(&input[r.span.clone()], r.text));
+    }
+
+    #[test]
+    fn records_without_trailing_newline() {
+        let texts = records("a\n\nb").map(|r| r.text).collect::<Vec<_>>();
+        assert_eq!(texts, vec!["a", "b"]);
+        assert_eq!(records("").count(), 0);
+        assert_eq!(records("\n \n\r\n").count(), 0);
+    }
+
+    #[test]
+    fn lines_are_parsed_with_locations() {
+        let parsed = parse_lines("1\n\n2\r\n", integers::<u8>);
+        assert_eq!(parsed.unwrap(), vec![vec![1], vec![2]]);
+        let err = parse_lines("1\n\n2 x\n", integers::<u8>).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid number \"x\" at line 3, column 3");
+        let record = records("a\n\nb:1 c\n").nth(1).unwrap();
+        let err = key_values(record.text)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| record.relocate(e))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Malformed field \"c\" at line 3, column 5, expected key:value"
+        );
+    }
+
+    #[test]
+    fn readers() {
+        assert_eq!(integers::<i32>("1, -2\n3,4").unwrap(), vec![1, -2, 3, 4]);
+        let field = key_values("a:b:c").next().unwrap().unwrap();
+        assert_eq!((field.key, field.value), ("a", "b:c"));
+        let grid = char_grid(" ab\nba \n", |c| Some(c == 'a'), "a or b").unwrap();
+        assert_eq!(grid, vec![vec![true, false], vec![false, true]]);
+        let err = char_grid("ab\nb\n", Some, "anything").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Malformed row \"b\" at line 2, column 1, expected 2 columns"
+        );
+        assert!(char_grid("\n", Some, "anything").is_err());
+    }
+}
@@ -0,0 +1,15 @@
+//! Solutions to Advent of Code 2020, along with the parsers and types each
+//! day's puzzle is built on.
+
+pub mod day1;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod error;
+pub mod generate;
+pub mod grid;
+pub mod input;
+pub mod runner;
+pub mod scaffold;
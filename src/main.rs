@@ -1,24 +1,185 @@
-use anyhow;
-
-mod day1;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-
-fn main() -> anyhow::Result<()> {
-    dbg!(day1::day_one_solution_one());
-    dbg!(day1::day_one_solution_two());
-    dbg!(day2::day_two_solution_one()?);
-    dbg!(day2::day_two_solution_two()?);
-    dbg!(day3::day_three_solution_one());
-    dbg!(day3::day_three_solution_two());
-    dbg!(day4::day_four_solution_one());
-    dbg!(day4::day_four_solution_two());
-    dbg!(day5::day_five_solution_one()?);
-    dbg!(day5::day_five_solution_two()?);
-    dbg!(day6::day_six_solution_one());
-    dbg!(day6::day_six_solution_two());
-    Ok(())
+use anyhow::{anyhow, Context, Error};
+
+use std::path::Path;
+
+use aoc_2020::{error::ParseError, generate, runner, scaffold};
+
+/// What to do, from the command line
+enum Command {
+    /// Run every solution, `jobs` at a time
+    Run { jobs: usize },
+    /// Create the files for a new day
+    New { day: u8 },
+    /// Check every solution against the puzzle's examples
+    Examples,
+    /// Print a random input for a day
+    Generate {
+        day: u8,
+        size: Option<usize>,
+        seed: u64,
+        invalid: bool,
+    },
+}
+impl Command {
+    fn parse(args: impl Iterator<Item = String>) -> Result<Self, Error> {
+        let mut args = args.peekable();
+        let command = match args.peek().map(String::as_str) {
+            Some("new") | Some("examples") | Some("generate") => args.next(),
+            _ => None,
+        };
+        let (mut jobs, mut day, mut size, mut seed, mut invalid) =
+            (1, None, None, 0, false);
+        while let Some(arg) = args.next() {
+            match command.as_deref() {
+                None if is_option(&arg, "--jobs", "-j") => {
+                    jobs = number(value(&arg, &mut args)?, "number of jobs")?
+                }
+                Some("new") | Some("generate") if is_option(&arg, "--day", "-d") => {
+                    day = Some(number(value(&arg, &mut args)?, "day")?)
+                }
+                Some("generate") if is_option(&arg, "--size", "-n") => {
+                    size = Some(number(value(&arg, &mut args)?, "size")?)
+                }
+                Some("generate") if is_option(&arg, "--seed", "-s") => {
+                    seed = number(value(&arg, &mut args)?, "seed")?
+                }
+                Some("generate") if arg == "--invalid" => invalid = true,
+                _ => return Err(anyhow!("Unknown argument {:?}", arg)),
+            }
+        }
+        let day = || {
+            day.ok_or_else(|| {
+                anyhow!("{} needs a --day", command.as_deref().unwrap_or(""))
+            })
+        };
+        match command.as_deref() {
+            None => Ok(Command::Run { jobs }),
+            Some("new") => Ok(Command::New { day: day()? }),
+            Some("generate") => Ok(Command::Generate {
+                day: day()?,
+                size,
+                seed,
+                invalid,
+            }),
+            _ => Ok(Command::Examples),
+        }
+    }
+}
+
+/// Whether `arg` is the option `long` or `short`, which can be given as
+/// `--long value`, `--long=value` or `-s value`
+fn is_option(arg: &str, long: &str, short: &str) -> bool {
+    arg == long
+        || arg == short
+        || arg
+            .strip_prefix(long)
+            .is_some_and(|rest| rest.starts_with('='))
+}
+
+/// The value of the option `arg`, which is either after an `=` or the next
+/// argument
+fn value(arg: &str, rest: &mut impl Iterator<Item = String>) -> Result<String, Error> {
+    match arg.split_once('=') {
+        Some((_, value)) => Ok(value.to_owned()),
+        None => rest.next().ok_or_else(|| anyhow!("{} needs a value", arg)),
+    }
+}
+
+fn number<T: std::str::FromStr>(value: String, what: &str) -> Result<T, Error>
+where
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    value
+        .parse()
+        .with_context(|| format!("Invalid {} {:?}", what, value))
+}
+
+/// Describe an error, showing where in the input parse errors came from
+fn describe(e: &Error) -> String {
+    match e.downcast_ref::<ParseError>() {
+        Some(parse_error) => format!("{:#}", parse_error),
+        None => format!("{:#}", e),
+    }
+}
+
+/// Run every solution, printing the answers and how long they took
+fn solve(jobs: usize) -> bool {
+    let report = runner::run(&runner::solutions(), jobs);
+    for outcome in &report.outcomes {
+        let task = format!(
+            "Day {} part {} ({:.2?})",
+            outcome.solution.day, outcome.solution.part, outcome.elapsed
+        );
+        match &outcome.answer {
+            Ok(answer) => println!("{}: {}", task, answer),
+            Err(e) => println!("{}: Error: {}", task, describe(e)),
+        }
+    }
+    println!(
+        "{} solutions in {:.2?} on {} thread(s), {:.2?} of work, {:.2}x parallelism",
+        report.outcomes.len(),
+        report.elapsed,
+        report.jobs,
+        report.busy(),
+        report.parallelism()
+    );
+    report.outcomes.iter().all(|o| o.answer.is_ok())
+}
+
+/// Check every solution against its examples, printing any that fail
+fn check_examples() -> bool {
+    let outcomes = runner::check_examples(&runner::solutions());
+    for outcome in &outcomes {
+        let example = format!(
+            "Day {} part {} example {}",
+            outcome.solution.day, outcome.solution.part, outcome.number
+        );
+        match &outcome.answer {
+            _ if outcome.passed() => println!("{}: ok", example),
+            Ok(answer) => {
+                println!("{}: expected {}, got {}", example, outcome.expected, answer)
+            }
+            Err(e) => println!("{}: Error: {}", example, describe(e)),
+        }
+    }
+    let passed = outcomes.iter().filter(|o| o.passed()).count();
+    println!("{} of {} examples passed", passed, outcomes.len());
+    passed == outcomes.len()
+}
+
+fn run() -> Result<bool, Error> {
+    match Command::parse(std::env::args().skip(1))? {
+        Command::Run { jobs } => Ok(solve(jobs)),
+        Command::Examples => Ok(check_examples()),
+        Command::Generate {
+            day,
+            size,
+            seed,
+            invalid,
+        } => {
+            let size = size
+                .or_else(|| generate::puzzle_size(day))
+                .ok_or_else(|| anyhow!("There's no generator for day {}", day))?;
+            print!("{}", generate::generate(day, size, seed, invalid)?);
+            Ok(true)
+        }
+        Command::New { day } => {
+            let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+            for path in scaffold::new_day(root, day)? {
+                println!("Wrote {}", path.display());
+            }
+            Ok(true)
+        }
+    }
+}
+
+fn main() {
+    match run() {
+        Ok(true) => {}
+        Ok(false) => std::process::exit(1),
+        Err(e) => {
+            eprintln!("Error: {}", describe(&e));
+            std::process::exit(1);
+        }
+    }
 }
@@ -0,0 +1,258 @@
+//! Running the solutions, optionally several at once
+
+use anyhow::Error;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// An example from a puzzle, along with its answer
+#[derive(Debug, Clone, Copy)]
+pub struct Example {
+    pub input: &'static str,
+    pub answer: usize,
+}
+
+/// One part of one day's puzzle
+#[derive(Debug, Clone, Copy)]
+pub struct Solution {
+    pub day: u8,
+    pub part: u8,
+    /// The puzzle input
+    pub input: &'static str,
+    pub solve: fn(&str) -> Result<usize, Error>,
+    /// The examples from the puzzle, to check `solve` against
+    pub examples: &'static [Example],
+}
+impl Solution {
+    /// Solve the puzzle input
+    pub fn run(&self) -> Result<usize, Error> {
+        (self.solve)(self.input)
+    }
+}
+
+/// Every solution, in day and part order
+pub fn solutions() -> Vec<Solution> {
+    [
+        crate::day1::SOLUTIONS,
+        crate::day2::SOLUTIONS,
+        crate::day3::SOLUTIONS,
+        crate::day4::SOLUTIONS,
+        crate::day5::SOLUTIONS,
+        crate::day6::SOLUTIONS,
+    ]
+    .concat()
+}
+
+/// What happened when a solution ran
+#[derive(Debug)]
+pub struct Outcome {
+    pub solution: Solution,
+    pub answer: Result<usize, Error>,
+    /// How long the solution took on its own thread
+    pub elapsed: Duration,
+}
+
+/// The outcomes of a run, in the same order as the solutions
+#[derive(Debug)]
+pub struct Report {
+    pub outcomes: Vec<Outcome>,
+    /// How many threads the solutions were spread over
+    pub jobs: usize,
+    /// How long the whole run took
+    pub elapsed: Duration,
+}
+impl Report {
+    /// The total time spent in solutions, across every thread
+    pub fn busy(&self) -> Duration {
+        self.outcomes.iter().map(|o| o.elapsed).sum()
+    }
+
+    /// How many solutions were running at once on average, which is the
+    /// time spent in solutions over the time the run took
+    ///
+    /// This isn't a speedup. Solutions take longer when their threads have
+    /// to share CPUs, which raises this without the run getting any faster.
+    pub fn parallelism(&self) -> f64 {
+        match self.elapsed.as_secs_f64() {
+            wall if wall > 0.0 => self.busy().as_secs_f64() / wall,
+            _ => 1.0,
+        }
+    }
+}
+
+/// Run the solutions on up to `jobs` threads
+///
+/// Each thread takes the next solution that hasn't been started yet, so a
+/// slow solution doesn't hold up the ones queued behind it. The outcomes
+/// come back in the same order as `solutions`, whatever order they finish.
+pub fn run(solutions: &[Solution], jobs: usize) -> Report {
+    let jobs = jobs.clamp(1, solutions.len().max(1));
+    let started = Instant::now();
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..jobs {
+            let sender = sender.clone();
+            let next = &next;
+            scope.spawn(move || loop {
+                let idx = next.fetch_add(1, Ordering::Relaxed);
+                let solution = match solutions.get(idx) {
+                    Some(solution) => solution,
+                    None => break,
+                };
+                let start = Instant::now();
+                let answer = solution.run();
+                let outcome = Outcome {
+                    solution: *solution,
+                    answer,
+                    elapsed: start.elapsed(),
+                };
+                sender
+                    .send((idx, outcome))
+                    .expect("the receiver is still around");
+            });
+        }
+    });
+    drop(sender);
+    let mut outcomes = receiver.into_iter().collect::<Vec<_>>();
+    outcomes.sort_by_key(|(idx, _)| *idx);
+    Report {
+        outcomes: outcomes.into_iter().map(|(_, outcome)| outcome).collect(),
+        jobs,
+        elapsed: started.elapsed(),
+    }
+}
+
+/// What happened when a solution was run on one of its examples
+#[derive(Debug)]
+pub struct ExampleOutcome {
+    pub solution: Solution,
+    /// Which of the solution's examples this was, from 1
+    pub number: usize,
+    pub expected: usize,
+    pub answer: Result<usize, Error>,
+}
+impl ExampleOutcome {
+    pub fn passed(&self) -> bool {
+        matches!(self.answer, Ok(answer) if answer == self.expected)
+    }
+}
+
+/// Run every solution on each of its examples, in order
+pub fn check_examples(solutions: &[Solution]) -> Vec<ExampleOutcome> {
+    solutions
+        .iter()
+        .flat_map(|solution| {
+            solution
+                .examples
+                .iter()
+                .enumerate()
+                .map(move |(idx, example)| ExampleOutcome {
+                    solution: *solution,
+                    number: idx + 1,
+                    expected: example.answer,
+                    answer: (solution.solve)(example.input),
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use anyhow::anyhow;
+
+    fn solution(
+        day: u8,
+        part: u8,
+        solve: fn(&str) -> Result<usize, Error>,
+    ) -> Solution {
+        Solution {
+            day,
+            part,
+            input: "",
+            solve,
+            examples: &[],
+        }
+    }
+
+    #[test]
+    fn outcomes_keep_their_order() {
+        let solutions = vec![
+            solution(1, 1, |_| {
+                thread::sleep(Duration::from_millis(20));
+                Ok(1)
+            }),
+            solution(1, 2, |_| Ok(2)),
+            solution(2, 1, |_| Err(anyhow!("no answer"))),
+        ];
+        for jobs in &[0, 1, 3, 8] {
+            let report = run(&solutions, *jobs);
+            assert_eq!(report.jobs, (*jobs).clamp(1, 3));
+            let answers = report
+                .outcomes
+                .iter()
+                .map(|o| o.answer.as_ref().ok().copied())
+                .collect::<Vec<_>>();
+            assert_eq!(answers, vec![Some(1), Some(2), None]);
+            assert!(report.busy() >= Duration::from_millis(20));
+        }
+        assert!(run(&[], 4).outcomes.is_empty());
+    }
+
+    #[test]
+    fn every_day_is_registered() {
+        let registered = solutions()
+            .iter()
+            .map(|s| (s.day, s.part))
+            .collect::<Vec<_>>();
+        let expected = (1..=6)
+            .flat_map(|day| vec![(day, 1), (day, 2)])
+            .collect::<Vec<_>>();
+        assert_eq!(registered[..expected.len()], expected[..]);
+        // days scaffolded since come after, in order, with both parts
+        let mut last = 6;
+        registered[expected.len()..].chunks(2).for_each(|parts| {
+            assert_eq!(parts, [(parts[0].0, 1), (parts[0].0, 2)]);
+            assert!(parts[0].0 > last);
+            last = parts[0].0;
+        });
+    }
+
+    #[test]
+    fn every_example_passes() {
+        check_examples(&solutions()).iter().for_each(|outcome| {
+            assert!(
+                outcome.passed(),
+                "day {} part {} example {}: expected {}, got {:?}",
+                outcome.solution.day,
+                outcome.solution.part,
+                outcome.number,
+                outcome.expected,
+                outcome.answer
+            )
+        });
+        let length = solution(1, 1, |input| Ok(input.len()));
+        static EXAMPLES: [Example; 2] = [
+            Example {
+                input: "abc",
+                answer: 3,
+            },
+            Example {
+                input: "",
+                answer: 1,
+            },
+        ];
+        let outcomes = check_examples(&[Solution {
+            examples: &EXAMPLES,
+            ..length
+        }]);
+        let passed = outcomes.iter().map(ExampleOutcome::passed);
+        assert_eq!(passed.collect::<Vec<_>>(), vec![true, false]);
+    }
+}
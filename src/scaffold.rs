@@ -0,0 +1,238 @@
+//! Generating the files for a new day
+
+use anyhow::{anyhow, Context, Error};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+const ONES: [&str; 20] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+
+/// The day as it's spelled in function names, e.g. `twenty_one`
+pub fn day_name(day: u8) -> String {
+    match day {
+        0..=19 => ONES[day as usize].to_owned(),
+        20 => "twenty".to_owned(),
+        _ => format!("twenty_{}", day_name(day - 20)),
+    }
+}
+
+/// The source of a new day's module: both parts, registered with the
+/// runner with the puzzle's example, and a test of them on it
+///
+/// Until the day is solved, both parts answer 0, which is also the
+/// placeholder answer for the example, so the crate's tests still pass.
+pub fn module_source(day: u8) -> String {
+    let name = day_name(day);
+    format!(
+        r#"use anyhow::Error;
+
+use crate::runner::{{Example, Solution}};
+
+const INPUT: &str = include_str!("day{day}.input");
+const EXAMPLE: &str = include_str!("day{day}.example");
+
+fn part_one(_input: &str) -> Result<usize, Error> {{
+    Ok(0)
+}}
+
+fn part_two(_input: &str) -> Result<usize, Error> {{
+    Ok(0)
+}}
+
+pub fn day_{name}_solution_one() -> Result<usize, Error> {{
+    part_one(INPUT)
+}}
+
+pub fn day_{name}_solution_two() -> Result<usize, Error> {{
+    part_two(INPUT)
+}}
+
+/// Both parts, for the runner
+pub const SOLUTIONS: [Solution; 2] = [
+    Solution {{
+        day: {day},
+        part: 1,
+        input: INPUT,
+        solve: part_one,
+        examples: &[Example {{
+            input: EXAMPLE,
+            answer: 0,
+        }}],
+    }},
+    Solution {{
+        day: {day},
+        part: 2,
+        input: INPUT,
+        solve: part_two,
+        examples: &[Example {{
+            input: EXAMPLE,
+            answer: 0,
+        }}],
+    }},
+];
+
+#[cfg(test)]
+mod test {{
+    use super::*;
+
+    #[test]
+    fn test_examples() {{
+        SOLUTIONS.iter().for_each(|solution| {{
+            solution.examples.iter().for_each(|example| {{
+                assert_eq!((solution.solve)(example.input).unwrap(), example.answer);
+            }})
+        }});
+    }}
+}}
+"#,
+        day = day,
+        name = name,
+    )
+}
+
+/// Insert `line` among the consecutive lines that look like it, keeping the
+/// days in order. `day_of` picks the day out of a line, if it's one of them.
+fn insert_line<F>(source: &str, line: &str, day: u8, day_of: F) -> Result<String, Error>
+where
+    F: Fn(&str) -> Option<u8>,
+{
+    let lines = source.lines().collect::<Vec<_>>();
+    let first = lines
+        .iter()
+        .position(|ln| day_of(ln).is_some())
+        .ok_or_else(|| anyhow!("Couldn't find where the days are listed"))?;
+    let count = lines[first..]
+        .iter()
+        .take_while(|ln| day_of(ln).is_some())
+        .count();
+    let listed = &lines[first..first + count];
+    if listed.iter().any(|ln| day_of(ln) == Some(day)) {
+        return Err(anyhow!("Day {} is already there", day));
+    }
+    let idx = first
+        + listed
+            .iter()
+            .take_while(|ln| day_of(ln) < Some(day))
+            .count();
+    let mut lines = lines.iter().map(|ln| ln.to_string()).collect::<Vec<_>>();
+    lines.insert(idx, line.to_owned());
+    Ok(lines.join("\n") + "\n")
+}
+
+/// Add `pub mod dayN;` to the library's module list
+pub fn register_module(lib_rs: &str, day: u8) -> Result<String, Error> {
+    insert_line(lib_rs, &format!("pub mod day{};", day), day, |ln| {
+        ln.strip_prefix("pub mod day")?
+            .strip_suffix(';')?
+            .parse()
+            .ok()
+    })
+}
+
+/// Add the day's solutions to the runner's registry
+pub fn register_solutions(runner_rs: &str, day: u8) -> Result<String, Error> {
+    let line = format!("        crate::day{}::SOLUTIONS,", day);
+    insert_line(runner_rs, &line, day, |ln| {
+        ln.trim()
+            .strip_prefix("crate::day")?
+            .strip_suffix("::SOLUTIONS,")?
+            .parse()
+            .ok()
+    })
+}
+
+/// Create a new day in the crate at `root`, returning the files that were
+/// created or changed
+///
+/// Nothing is written unless everything is ready to be, so a failure leaves
+/// the crate as it was.
+pub fn new_day(root: &Path, day: u8) -> Result<Vec<PathBuf>, Error> {
+    if !(1..=25).contains(&day) {
+        return Err(anyhow!("Day {} isn't between 1 and 25", day));
+    }
+    let src = root.join("src");
+    let module = src.join(format!("day{}.rs", day));
+    let input = src.join(format!("day{}.input", day));
+    let example = src.join(format!("day{}.example", day));
+    if let Some(existing) = [&module, &input, &example]
+        .iter()
+        .find(|path| path.exists())
+    {
+        return Err(anyhow!("{} already exists", existing.display()));
+    }
+    let read = |path: &Path| {
+        fs::read_to_string(path)
+            .with_context(|| format!("Couldn't read {}", path.display()))
+    };
+    let (lib, runner) = (src.join("lib.rs"), src.join("runner.rs"));
+    let changes = vec![
+        (module, module_source(day)),
+        (input, String::new()),
+        (example, String::new()),
+        (lib.clone(), register_module(&read(&lib)?, day)?),
+        (runner.clone(), register_solutions(&read(&runner)?, day)?),
+    ];
+    changes
+        .into_iter()
+        .map(|(path, contents)| {
+            fs::write(&path, contents)
+                .with_context(|| format!("Couldn't write {}", path.display()))?;
+            Ok(path)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn day_names() {
+        assert_eq!(day_name(7), "seven");
+        assert_eq!(day_name(20), "twenty");
+        assert_eq!(day_name(25), "twenty_five");
+        let source = module_source(12);
+        assert!(source.contains("pub fn day_twelve_solution_two()"));
+        assert!(source.contains("include_str!(\"day12.example\")"));
+        assert!(source.contains("input: EXAMPLE"));
+        assert!(!source.contains("#[ignore"));
+    }
+
+    #[test]
+    fn days_are_registered_in_order() {
+        let lib = "//! docs\n\npub mod day1;\npub mod day3;\npub mod error;\n";
+        assert_eq!(
+            register_module(lib, 2).unwrap(),
+            "//! docs\n\npub mod day1;\npub mod day2;\npub mod day3;\npub mod error;\n"
+        );
+        assert!(register_module(lib, 3).is_err());
+        let runner = "    [\n        crate::day1::SOLUTIONS,\n    ]\n";
+        assert_eq!(
+            register_solutions(runner, 7).unwrap(),
+            "    [\n        crate::day1::SOLUTIONS,\n        crate::day7::SOLUTIONS,\n    ]\n"
+        );
+        assert!(register_solutions("", 7).is_err());
+    }
+}